@@ -0,0 +1,116 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! Compares the previous `Vec<String>`-joining renderer with the writer-based one.
+//!
+//! Run with `cargo bench --bench render`. Every path draws its UUIDs from the
+//! same seeded source, so the difference is the cost of rendering alone.
+
+use guardgen_lib::{
+    FixedClock, IncludeGuardGenerator, Language, LineEnding, RandomSource, SeededRng, UuidKind,
+};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 200_000;
+
+fn generator() -> IncludeGuardGenerator {
+    IncludeGuardGenerator::with_sources(FixedClock::new(1_700_000_000, 0), SeededRng::new(1))
+}
+
+/// The renderer as it was before `write_guard`, kept here as the baseline.
+fn legacy_render(prefix: String, suffix: Option<String>, rng: &mut SeededRng) -> String {
+    let mut bytes = [0u8; 16];
+    rng.fill_bytes(&mut bytes);
+    let uuid_string = uuid::Builder::from_random_bytes(bytes)
+        .into_uuid()
+        .to_string();
+
+    let uuid = uuid_string.replace('-', "_").to_uppercase();
+    let mut guard = vec![prefix, uuid.clone()];
+    if let Some(s) = &suffix {
+        guard.push(s.clone());
+    }
+    let guard = guard.join("_");
+
+    let mut text = vec![format!("#ifndef {}", guard), format!("#define {}", guard)];
+    let extern_c: Vec<String> = vec![
+        "".to_string(),
+        "#ifdef __cplusplus".to_string(),
+        "extern \"C\" {".to_string(),
+        "#endif /* __cplusplus */".to_string(),
+        "".to_string(),
+        "#ifdef __cplusplus".to_string(),
+        "} /* extern \"C\" */".to_string(),
+        "#endif /* __cplusplus */".to_string(),
+        "".to_string(),
+    ];
+    text.extend(extern_c);
+    text.push(format!("#endif /* {} */", guard));
+    text.push("".to_string());
+    let newline = "\n".to_string();
+    text.join(&newline)
+}
+
+fn bench(name: &str, mut body: impl FnMut()) -> Duration {
+    // Warm up caches and the allocator before timing.
+    for _ in 0..ITERATIONS / 10 {
+        body();
+    }
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        body();
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:<28} {:>8.1} ns/guard",
+        name,
+        elapsed.as_nanos() as f64 / f64::from(ITERATIONS)
+    );
+    elapsed
+}
+
+fn main() {
+    let mut rng = SeededRng::new(1);
+    let legacy = bench("legacy Vec<String> join", || {
+        black_box(legacy_render(
+            black_box("ACME").to_string(),
+            Some(black_box("H").to_string()),
+            &mut rng,
+        ));
+    });
+
+    let mut string_generator = generator();
+    let generate = bench("generate (String)", || {
+        black_box(string_generator.generate(
+            black_box("ACME").to_string(),
+            Some(black_box("H").to_string()),
+            Language::C,
+            LineEnding::LF,
+            UuidKind::V4,
+        ));
+    });
+
+    let mut writer_generator = generator();
+    let mut buffer = String::with_capacity(512);
+    let write_guard = bench("write_guard (reused buffer)", || {
+        buffer.clear();
+        writer_generator
+            .write_guard(
+                &mut buffer,
+                black_box("ACME"),
+                Some(black_box("H")),
+                Language::C,
+                LineEnding::LF,
+                UuidKind::V4,
+            )
+            .unwrap();
+        black_box(&buffer);
+    });
+
+    println!(
+        "speedup over legacy: generate {:.2}x, write_guard {:.2}x",
+        legacy.as_secs_f64() / generate.as_secs_f64(),
+        legacy.as_secs_f64() / write_guard.as_secs_f64()
+    );
+}
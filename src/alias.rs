@@ -0,0 +1,156 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! Human-readable alias macros defined inside UUID guards, such as
+//! `ACME_NET_SOCKET_H_INCLUDED`, so other code can test whether a header was included.
+//!
+//! An alias is built from a template with these placeholders:
+//!
+//! - `{{path}}`: the path below the last `include` directory, e.g. `ACME_NET_SOCKET_H`
+//! - `{{file}}`: the file name, e.g. `SOCKET_H`
+//! - `{{stem}}`: the file name without its extension, e.g. `SOCKET`
+//!
+//! An existing header's alias is recognised by the `_INCLUDED` suffix of the
+//! default template; other aliases carry an `ALIAS_MARKER` comment so a
+//! feature macro such as `_GNU_SOURCE` is never mistaken for one.
+
+use crate::namespace::{INCLUDE_DIR, is_identifier, sanitize};
+use std::fmt;
+use std::path::{Component, Path};
+
+/// Template of the alias when none is given.
+pub const DEFAULT_TEMPLATE: &str = "{{path}}_INCLUDED";
+
+/// Suffix of the aliases `DEFAULT_TEMPLATE` produces.
+pub const ALIAS_SUFFIX: &str = "_INCLUDED";
+
+/// Comment text marking the `#define` of an alias without `ALIAS_SUFFIX`.
+pub const ALIAS_MARKER: &str = "guardgen: alias";
+
+/// Whether the `#define` of `alias` needs an `ALIAS_MARKER` comment to be recognised.
+pub fn needs_marker(alias: &str) -> bool {
+    !alias.ends_with(ALIAS_SUFFIX)
+}
+
+/// Error deriving an alias.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AliasError {
+    /// `{{name}}` is not a known placeholder.
+    UnknownPlaceholder(String),
+    /// A `{{` is never closed.
+    UnterminatedPlaceholder,
+    /// The template uses a placeholder but there is no file name to derive it from.
+    MissingPath,
+    /// The result is not a valid macro name.
+    InvalidMacro(String),
+}
+
+impl fmt::Display for AliasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AliasError::UnknownPlaceholder(name) => {
+                write!(f, "unknown alias placeholder '{{{{{}}}}}'", name)
+            }
+            AliasError::UnterminatedPlaceholder => write!(f, "unterminated alias placeholder"),
+            AliasError::MissingPath => write!(f, "the alias needs a file name"),
+            AliasError::InvalidMacro(name) => write!(f, "'{}' is not a valid macro name", name),
+        }
+    }
+}
+
+impl std::error::Error for AliasError {}
+
+/// The alias of the header at `path`, built from `template`.
+///
+/// Placeholder values are upper-cased and characters invalid in identifiers
+/// become `_`; the rest of the template is kept as is.
+///
+/// @post `Ok` holds a valid macro name.
+pub fn alias_for(path: &Path, template: &str) -> Result<String, AliasError> {
+    let mut alias = String::with_capacity(template.len() + 32);
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        alias.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find("}}")
+            .ok_or(AliasError::UnterminatedPlaceholder)?;
+        let name = rest[start + 2..start + end].trim();
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or(AliasError::MissingPath)?;
+        let value = match name {
+            "path" => below_include(path).join("_"),
+            "file" => file_name.to_string(),
+            "stem" => file_name.split('.').next().unwrap_or_default().to_string(),
+            name => return Err(AliasError::UnknownPlaceholder(name.to_string())),
+        };
+        alias.push_str(&sanitize(&value).to_ascii_uppercase());
+        rest = &rest[start + end + 2..];
+    }
+    alias.push_str(rest);
+    if is_identifier(&alias) {
+        Ok(alias)
+    } else {
+        Err(AliasError::InvalidMacro(alias))
+    }
+}
+
+/// The components of `path` below its last `include` directory; all of them
+/// for a relative path without one, and only the file name for an absolute path.
+fn below_include(path: &Path) -> Vec<&str> {
+    let components: Vec<&str> = path
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .collect();
+    let last = components.len().saturating_sub(1);
+    match components[..last]
+        .iter()
+        .rposition(|dir| *dir == INCLUDE_DIR)
+    {
+        Some(i) => components[i + 1..].to_vec(),
+        None if path.is_absolute() => components[last..].to_vec(),
+        None => components,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn derives_aliases_from_paths_and_templates() {
+        let path = Path::new("lib/include/acme/net/socket.h");
+        assert_eq!(
+            alias_for(path, DEFAULT_TEMPLATE),
+            Ok("ACME_NET_SOCKET_H_INCLUDED".to_string())
+        );
+        assert_eq!(
+            alias_for(path, "HAVE_{{stem}}"),
+            Ok("HAVE_SOCKET".to_string())
+        );
+        assert_eq!(
+            alias_for(Path::new("/abs/3d-math.hpp"), "{{path}}"),
+            Ok("_3D_MATH_HPP".to_string())
+        );
+        assert_eq!(
+            alias_for(Path::new(""), DEFAULT_TEMPLATE),
+            Err(AliasError::MissingPath)
+        );
+        assert_eq!(
+            alias_for(path, "{{dir}}"),
+            Err(AliasError::UnknownPlaceholder("dir".to_string()))
+        );
+        assert_eq!(
+            alias_for(path, "acme-{{file}}"),
+            Err(AliasError::InvalidMacro("acme-SOCKET_H".to_string()))
+        );
+    }
+}
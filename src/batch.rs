@@ -0,0 +1,161 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! Batch generation of include guards that are distinct within the batch.
+
+use crate::render::render_guard;
+use crate::{GuardOptions, IncludeGuardGenerator};
+use std::collections::HashSet;
+use std::io;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen::prelude::*;
+
+// A duplicate can only come from a degenerate injected random source or clock;
+// give up after this many consecutive retries instead of looping forever.
+const MAX_ATTEMPTS: usize = 64;
+
+/// Lazy, unbounded iterator over include guards that are distinct from each other.
+///
+/// Created by `IncludeGuardGenerator::guards`. Each item is the result of one
+/// `try_generate`-equivalent call. The iterator is fused on error: after the
+/// first `Err` it yields `None`.
+///
+/// @invariant No two `Ok` items yielded by one iterator are equal.
+pub struct Guards<'a> {
+    generator: &'a mut IncludeGuardGenerator,
+    options: &'a GuardOptions,
+    seen: HashSet<uuid::Uuid>,
+    failed: bool,
+}
+
+impl Iterator for Guards<'_> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        for _ in 0..MAX_ATTEMPTS {
+            let uuid = match self.generator.next_uuid(self.options.uuid_kind) {
+                Ok(uuid) => uuid,
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(e));
+                }
+            };
+            if self.seen.insert(uuid) {
+                return Some(Ok(render_guard(
+                    &uuid,
+                    &self.options.prefix,
+                    self.options.suffix.as_deref(),
+                    self.options.language,
+                    self.options.line_ending,
+                )));
+            }
+        }
+        self.failed = true;
+        Some(Err(io::Error::other(
+            "the random source keeps producing duplicate UUIDs",
+        )))
+    }
+}
+
+impl IncludeGuardGenerator {
+    /// Iterate lazily over guards rendered with `options`, all distinct from each other.
+    ///
+    /// @post The iterator only ends after yielding an error; bound it with `take`.
+    pub fn guards<'a>(&'a mut self, options: &'a GuardOptions) -> Guards<'a> {
+        Guards {
+            generator: self,
+            options,
+            seen: HashSet::new(),
+            failed: false,
+        }
+    }
+
+    /// Generate `count` guards rendered with `options`, all distinct from each other.
+    ///
+    /// @post On success the vector holds exactly `count` pairwise distinct guards,
+    ///       in generation order (ascending for UUID v7).
+    pub fn generate_many(
+        &mut self,
+        count: usize,
+        options: &GuardOptions,
+    ) -> io::Result<Vec<String>> {
+        self.guards(options).take(count).collect()
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+#[wasm_bindgen]
+impl IncludeGuardGenerator {
+    /// Generate `count` distinct guards in a single call across the JS boundary.
+    #[wasm_bindgen(js_name = generateMany)]
+    pub fn generate_many_js(
+        &mut self,
+        count: usize,
+        options: &GuardOptions,
+    ) -> Result<Vec<String>, JsError> {
+        self.generate_many(count, options)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::extract_uuids;
+    use crate::{FixedClock, GuardOptions, IncludeGuardGenerator, Language, LineEnding, UuidKind};
+
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn generate_many_returns_distinct_guards() {
+        let mut generator = IncludeGuardGenerator::new();
+        let options = GuardOptions::new(
+            "TEST".to_string(),
+            Some("H".to_string()),
+            Language::C,
+            LineEnding::LF,
+            UuidKind::V7,
+        );
+
+        let guards = generator.generate_many(256, &options).unwrap();
+        assert_eq!(guards.len(), 256);
+
+        let uuids: Vec<String> = guards
+            .iter()
+            .map(|guard| extract_uuids(guard.as_str())[0].clone())
+            .collect();
+        let distinct: std::collections::HashSet<&String> = uuids.iter().collect();
+        assert_eq!(distinct.len(), 256);
+        assert!(uuids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(guards.iter().all(|guard| guard.contains("extern \"C\" {")));
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn guards_iterator_retries_and_stops_after_duplicates() {
+        // A random source repeating every other call forces a retry per guard.
+        let mut flip = false;
+        let rng = move |dest: &mut [u8]| {
+            flip = !flip;
+            dest.fill(if flip { 1 } else { 2 });
+        };
+        let mut generator = IncludeGuardGenerator::with_sources(FixedClock::new(0, 0), rng);
+        let options = GuardOptions {
+            uuid_kind: UuidKind::V4,
+            ..GuardOptions::default()
+        };
+
+        let mut guards = generator.guards(&options);
+        assert!(guards.next().unwrap().is_ok());
+        assert!(guards.next().unwrap().is_ok());
+        // Only two distinct UUIDs exist for this source.
+        assert!(guards.next().unwrap().is_err());
+        // The iterator is fused after its first error.
+        assert!(guards.next().is_none());
+    }
+}
@@ -0,0 +1,396 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! Implementations of the `guardgen` subcommands.
+//!
+//! Each subcommand reports its own errors on stderr and returns the process
+//! exit code: 0 on success, 1 when anything failed or (for `check`) when a
+//! problem was found. Commands that write files do so through a
+//! `FileWriter`, which can preview the changes instead.
+
+pub mod add;
+pub mod check;
+pub mod convert;
+mod diff;
+pub mod fix;
+pub mod inspect;
+mod journal;
+pub mod link;
+pub mod new;
+pub mod undo;
+
+use guardgen_lib::LineEnding;
+use guardgen_lib::header::{LineEndings, detect_line_endings};
+use journal::Journal;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Read a header as UTF-8 text.
+fn read_header(path: &str) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| io_error(path, &e, "reading"))
+}
+
+/// What happens to the file changes a command computes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WriteMode {
+    /// Write the changes to disk.
+    #[default]
+    Write,
+    /// Print a unified diff of the changes to stdout.
+    DryRun,
+    /// Only report which files would change.
+    Check,
+}
+
+/// Carries out, or previews, the file changes of one run.
+///
+/// In the preview modes nothing on disk is touched and the commands' status
+/// messages are suppressed, so the output of `--dry-run` is a plain patch.
+///
+/// When writing, every replaced or created file is recorded in the run's
+/// journal, so `guardgen undo` can restore or remove it.
+pub struct FileWriter {
+    mode: WriteMode,
+    changed: usize,
+    journal_dir: PathBuf,
+    journal: Option<Journal>,
+}
+
+impl FileWriter {
+    pub fn new(mode: WriteMode) -> Self {
+        FileWriter {
+            mode,
+            changed: 0,
+            journal_dir: journal::default_dir(),
+            journal: None,
+        }
+    }
+
+    /// Print a status message about a change that was written.
+    fn status(&self, message: &str) {
+        if self.mode == WriteMode::Write {
+            println!("{}", message);
+        }
+    }
+
+    /// Create `path` with `contents`; an existing file is only replaced with `overwrite`.
+    fn create(
+        &mut self,
+        path: &str,
+        contents: &str,
+        overwrite: bool,
+        parents: bool,
+    ) -> Result<(), String> {
+        let already_exists = || {
+            format!(
+                "Error: File '{}' already exists. Use --overwrite to overwrite.",
+                path
+            )
+        };
+        if self.mode != WriteMode::Write {
+            let old = match fs::read_to_string(path) {
+                Ok(_) if !overwrite => return Err(already_exists()),
+                Ok(old) => Some(old),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+                Err(e) => return Err(io_error(path, &e, "reading")),
+            };
+            self.preview(path, old.as_deref(), contents);
+            return Ok(());
+        }
+
+        if parents
+            && let Some(parent) = Path::new(path).parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Error creating directory '{}': {}", parent.display(), e))?;
+        }
+        let old = match fs::read(path) {
+            Ok(_) if !overwrite => return Err(already_exists()),
+            Ok(old) => Some(old),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(io_error(path, &e, "reading")),
+        };
+        let result = match &old {
+            Some(old) => {
+                self.record(path, Some(old), contents.as_bytes())?;
+                replace_file(Path::new(path), contents.as_bytes())
+            }
+            // Recorded once the file exists, so the journal never claims a
+            // file created by someone else.
+            None => create_file(Path::new(path), contents.as_bytes()),
+        };
+        result.map_err(|e| match e.kind() {
+            io::ErrorKind::AlreadyExists => already_exists(),
+            _ if old.is_some() => io_error(path, &e, "writing to"),
+            _ => io_error(path, &e, "creating"),
+        })?;
+        if old.is_none() {
+            self.record(path, None, contents.as_bytes())?;
+        }
+        self.changed += 1;
+        Ok(())
+    }
+
+    /// Replace the content `old` of the existing file `path` with `new`.
+    fn modify(&mut self, path: &str, old: &str, new: &str) -> Result<(), String> {
+        if old == new {
+            return Ok(());
+        }
+        if self.mode != WriteMode::Write {
+            self.preview(path, Some(old), new);
+            return Ok(());
+        }
+        self.record(path, Some(old.as_bytes()), new.as_bytes())?;
+        replace_file(Path::new(path), new.as_bytes())
+            .map_err(|e| io_error(path, &e, "writing to"))?;
+        self.changed += 1;
+        Ok(())
+    }
+
+    fn preview(&mut self, path: &str, old: Option<&str>, new: &str) {
+        if old == Some(new) {
+            return;
+        }
+        self.changed += 1;
+        match self.mode {
+            WriteMode::DryRun => print!("{}", diff::unified_diff(path, old, new)),
+            WriteMode::Check => println!("Would change '{}'.", path),
+            WriteMode::Write => unreachable!("previews are only made in preview modes"),
+        }
+    }
+
+    /// Record the change of `path` in the journal, starting it on first use.
+    fn record(&mut self, path: &str, old: Option<&[u8]>, new: &[u8]) -> Result<(), String> {
+        let journal = match &mut self.journal {
+            Some(journal) => journal,
+            None => self.journal.insert(
+                Journal::start(&self.journal_dir)
+                    .map_err(|e| format!("Error creating the backup journal: {}", e))?,
+            ),
+        };
+        journal
+            .record(Path::new(path), old, new)
+            .map_err(|e| format!("Error recording a backup of '{}': {}", path, e))
+    }
+
+    /// Finish the run and return its exit code; `failed` tells whether any file failed.
+    ///
+    /// @post With `WriteMode::Check`, any pending change also yields 1.
+    fn finish(self, failed: bool) -> i32 {
+        if let Some(journal) = &self.journal {
+            self.status(&format!(
+                "Changes recorded as run '{}'; revert them with `guardgen undo`.",
+                journal.run_id()
+            ));
+        }
+        i32::from(failed || (self.mode == WriteMode::Check && self.changed > 0))
+    }
+}
+
+/// Format Unix milliseconds as an ISO 8601 UTC timestamp.
+pub fn format_utc(millis: u64) -> String {
+    let secs_of_day = millis / 1000 % 86_400;
+    let (year, month, day) = civil_date(millis);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60,
+        millis % 1000
+    )
+}
+
+/// The UTC `(year, month, day)` of Unix milliseconds.
+pub fn civil_date(millis: u64) -> (i64, i64, i64) {
+    let days = millis / 1000 / 86_400;
+
+    // Civil-from-days (Howard Hinnant), valid for all non-negative day counts.
+    let z = days as i64 + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Describe an I/O error on `path`, e.g. `action` = "writing to".
+fn io_error(path: &str, e: &io::Error, action: &str) -> String {
+    match e.kind() {
+        io::ErrorKind::PermissionDenied => {
+            format!("Error: Permission denied when accessing '{}'.", path)
+        }
+        _ => format!("Error {} file '{}': {}", action, path, e),
+    }
+}
+
+/// Create `path` with `contents`, failing with `AlreadyExists` if it exists.
+///
+/// The existence check and the creation are one operation, so a file created
+/// concurrently is never truncated. A file whose write fails is removed again.
+fn create_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    file.write_all(contents)
+        .and_then(|()| file.sync_all())
+        .inspect_err(|_| {
+            let _ = fs::remove_file(path);
+        })
+}
+
+/// Replace (or create) `path` with `contents` so readers see either the old or the new file.
+///
+/// The contents go to a temporary file next to `path`, which is then renamed
+/// over it. An existing file's permissions are carried over.
+///
+/// @post On error, `path` is unchanged and no temporary file is left behind.
+fn replace_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let permissions = match fs::metadata(path) {
+        Ok(metadata) => Some(metadata.permissions()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e),
+    };
+    let (temp_path, mut file) = create_temp_file(path)?;
+    let result = file
+        .write_all(contents)
+        .and_then(|()| match permissions {
+            Some(permissions) => file.set_permissions(permissions),
+            None => Ok(()),
+        })
+        .and_then(|()| file.sync_all())
+        .and_then(|()| {
+            drop(file);
+            fs::rename(&temp_path, path)
+        });
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Exclusively create a uniquely named temporary file in the directory of `path`.
+fn create_temp_file(path: &Path) -> io::Result<(PathBuf, File)> {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    loop {
+        let mut temp_name = std::ffi::OsString::from(".");
+        temp_name.push(name);
+        temp_name.push(format!(
+            ".{}.{}.tmp",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let temp_path = path.with_file_name(temp_name);
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)
+        {
+            Ok(file) => return Ok((temp_path, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Line ending for text inserted into `text`: the explicit choice, else the file's own style.
+fn line_ending_for(text: &str, explicit: Option<LineEnding>) -> LineEnding {
+    explicit.unwrap_or(match detect_line_endings(text) {
+        LineEndings::LF => LineEnding::LF,
+        LineEndings::CRLF => LineEnding::CRLF,
+        LineEndings::None | LineEndings::Mixed => LineEnding::None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh, empty directory for one test.
+    pub(crate) fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("guardgen-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Parse the subcommand `argv` with an empty configuration in `dir`, so no
+    /// `guardgen.toml` around the test run applies.
+    pub(crate) fn parse_command(dir: &Path, argv: &[&str]) -> crate::Command {
+        use clap::Parser;
+        let config = dir.join("guardgen.toml");
+        fs::write(&config, "").unwrap();
+        let mut args = vec!["guardgen", argv[0], "--config", config.to_str().unwrap()];
+        args.extend(&argv[1..]);
+        crate::Cli::try_parse_from(args).unwrap().command.unwrap()
+    }
+
+    #[test]
+    fn create_file_never_touches_existing_files() {
+        let dir = scratch_dir("create");
+        let path = dir.join("a.h");
+
+        create_file(&path, b"first").unwrap();
+        let err = create_file(&path, b"second").unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read(&path).unwrap(), b"first");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn replace_file_swaps_contents_without_leftovers() {
+        let dir = scratch_dir("replace");
+        let path = dir.join("a.h");
+
+        replace_file(&path, b"first").unwrap();
+        replace_file(&path, b"second").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn preview_modes_leave_disk_alone() {
+        let dir = scratch_dir("preview");
+        let existing = dir.join("a.h");
+        fs::write(&existing, "old\n").unwrap();
+        let existing = existing.to_str().unwrap();
+        let missing = dir.join("sub").join("b.h");
+        let missing = missing.to_str().unwrap();
+
+        for mode in [WriteMode::DryRun, WriteMode::Check] {
+            let mut writer = FileWriter::new(mode);
+            writer.modify(existing, "old\n", "new\n").unwrap();
+            writer.create(missing, "new\n", false, true).unwrap();
+            assert!(writer.create(existing, "new\n", false, false).is_err());
+            assert_eq!(writer.changed, 2);
+            assert!(writer.journal.is_none());
+            assert_eq!(writer.finish(false), i32::from(mode == WriteMode::Check));
+        }
+
+        assert_eq!(fs::read_to_string(existing).unwrap(), "old\n");
+        assert!(!dir.join("sub").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn formats_v7_timestamps_in_utc() {
+        assert_eq!(format_utc(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(format_utc(1_700_000_000_123), "2023-11-14T22:13:20.123Z");
+        assert_eq!(format_utc(951_782_400_000), "2000-02-29T00:00:00.000Z");
+    }
+}
@@ -0,0 +1,124 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! `guardgen add`: put a fresh guard around headers that have none.
+
+use super::{FileWriter, line_ending_for, read_header};
+use crate::ModifyArgs;
+use guardgen_lib::header::{HeaderInfo, add_guard};
+use guardgen_lib::spdx::insert_missing;
+
+/// Run `add` and return the exit code.
+///
+/// Headers that already have a guard (or `#pragma once`, or the unguarded
+/// marker) are left untouched.
+/// With SPDX options, the headers it guards also get the SPDX lines they lack;
+/// `fix` adds them to headers that are already guarded.
+pub fn run(args: &ModifyArgs) -> i32 {
+    let guard_args = &args.guard;
+    let prepared = guard_args
+        .generator(args.write.mode())
+        .and_then(|generator| {
+            let config = guard_args.config()?;
+            let spdx = args.spdx.resolve(config.spdx, guard_args)?;
+            let linkage = guard_args.linkage(config.linkage)?;
+            Ok((generator, spdx, linkage, config.languages))
+        });
+    let (mut generator, spdx, linkage, languages) = match prepared {
+        Ok(prepared) => prepared,
+        Err(message) => {
+            eprintln!("{}", message);
+            return 1;
+        }
+    };
+
+    let mut writer = FileWriter::new(args.write.mode());
+    let mut failed = false;
+    for path in &args.files {
+        let result = read_header(path).and_then(|text| {
+            let info = HeaderInfo::analyze(&text);
+            if let Some(guard) = info.guard {
+                writer.status(&format!(
+                    "Skipped '{}': already guarded by '{}'.",
+                    path, guard.name
+                ));
+                return Ok(());
+            }
+            if info.pragma_once {
+                writer.status(&format!("Skipped '{}': uses #pragma once.", path));
+                return Ok(());
+            }
+            if info.unguarded {
+                writer.status(&format!(
+                    "Skipped '{}': marked as intentionally unguarded.",
+                    path
+                ));
+                return Ok(());
+            }
+            let language = guard_args.language_for(path, Some(&text), &languages);
+            let mut guard = generator
+                .try_next_guard(
+                    guard_args.prefix(),
+                    guard_args.suffix.as_deref(),
+                    language,
+                    line_ending_for(&text, guard_args.line_ending.map(Into::into)),
+                    guard_args.uuid_kind(),
+                )
+                .map_err(|e| guard_args.generation_error(e))?;
+            guard.set_linkage(linkage.clone());
+            let body = match &spdx {
+                Some(spdx) => insert_missing(&text, spdx, language),
+                None => text.clone(),
+            };
+            writer.modify(path, &text, &add_guard(&body, &guard))?;
+            writer.status(&format!("Guard '{}' added to '{}'.", guard.as_str(), path));
+            Ok(())
+        });
+        if let Err(message) = result {
+            eprintln!("{}", message);
+            failed = true;
+        }
+    }
+    writer.finish(failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Command;
+    use crate::commands::tests::{parse_command, scratch_dir};
+    use std::fs;
+
+    #[test]
+    fn skips_pragma_once_and_guarded_headers() {
+        let dir = scratch_dir("add");
+        let files = [
+            ("once.h", "#pragma once\nint f();\n"),
+            ("guarded.h", "#ifndef A_H\n#define A_H\n#endif\n"),
+            ("marked.h", "/* guardgen: unguarded */\nX(a)\n"),
+            ("plain.h", "int f();\n"),
+        ];
+        for (name, text) in files {
+            fs::write(dir.join(name), text).unwrap();
+        }
+        let add = |name: &str| {
+            let path = dir.join(name);
+            let Command::Add(args) =
+                parse_command(&dir, &["add", "--check", path.to_str().unwrap()])
+            else {
+                unreachable!("parsed as add");
+            };
+            run(&args)
+        };
+
+        assert_eq!(add("once.h"), 0);
+        assert_eq!(add("guarded.h"), 0);
+        assert_eq!(add("marked.h"), 0);
+        // --check exits with 1 for the one header that would change, and writes nothing.
+        assert_eq!(add("plain.h"), 1);
+        for (name, text) in files {
+            assert_eq!(fs::read_to_string(dir.join(name)).unwrap(), text);
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
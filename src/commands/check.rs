@@ -0,0 +1,111 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! `guardgen check`: report headers with missing or malformed guards.
+
+use super::read_header;
+use crate::FilesArgs;
+use guardgen_lib::header::{HeaderInfo, check_header};
+use std::collections::HashMap;
+
+/// Run `check` and return the exit code.
+///
+/// Besides the per-file checks, a guard or alias macro shared by several of
+/// the given files is reported for each of them.
+pub fn run(args: &FilesArgs) -> i32 {
+    let linkage = args
+        .guard
+        .config()
+        .and_then(|config| args.guard.linkage(config.linkage));
+    let options = match linkage {
+        Ok(linkage) => args.guard.check_options(linkage),
+        Err(message) => {
+            eprintln!("{}", message);
+            return 1;
+        }
+    };
+    let mut code = 0;
+    let mut owners: HashMap<String, Vec<&str>> = HashMap::new();
+    let mut alias_owners: HashMap<String, Vec<&str>> = HashMap::new();
+
+    for path in &args.files {
+        let text = match read_header(path) {
+            Ok(text) => text,
+            Err(message) => {
+                eprintln!("{}", message);
+                code = 1;
+                continue;
+            }
+        };
+        for issue in check_header(&text, &options) {
+            println!("{}: {}", path, issue);
+            code = 1;
+        }
+        if let Some(guard) = HeaderInfo::analyze(&text).guard {
+            owners.entry(guard.name).or_default().push(path);
+            if let Some(alias) = guard.alias {
+                alias_owners.entry(alias).or_default().push(path);
+            }
+        }
+    }
+
+    for path in &args.files {
+        for (kind, owners) in [("include guard", &owners), ("alias", &alias_owners)] {
+            for (name, paths) in owners {
+                if paths.len() > 1 && paths.contains(&path.as_str()) {
+                    let others: Vec<&str> = paths.iter().copied().filter(|p| p != path).collect();
+                    println!(
+                        "{}: {} '{}' is also used by {}",
+                        path,
+                        kind,
+                        name,
+                        others.join(", ")
+                    );
+                    code = 1;
+                }
+            }
+        }
+    }
+
+    if code == 0 {
+        println!("{} file(s) checked, no problems found.", args.files.len());
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Command;
+    use crate::commands::tests::{parse_command, scratch_dir};
+    use std::fs;
+
+    #[test]
+    fn exits_non_zero_on_problems() {
+        let dir = scratch_dir("check");
+        let guard = "ACME_0190F4C3_1A2B_7C3D_8E4F_5A6B7C8D9E0F_H";
+        let guarded = format!("#ifndef {0}\n#define {0}\n#endif\n", guard);
+        fs::write(dir.join("a.h"), &guarded).unwrap();
+        fs::write(dir.join("b.h"), &guarded).unwrap();
+        fs::write(dir.join("plain.h"), "int f();\n").unwrap();
+        let check = |names: &[&str]| {
+            let paths: Vec<String> = names
+                .iter()
+                .map(|name| dir.join(name).to_str().unwrap().to_string())
+                .collect();
+            let mut argv = vec!["check"];
+            argv.extend(paths.iter().map(String::as_str));
+            let Command::Check(args) = parse_command(&dir, &argv) else {
+                unreachable!("parsed as check");
+            };
+            run(&args)
+        };
+
+        assert_eq!(check(&["a.h"]), 0);
+        assert_eq!(check(&["plain.h"]), 1);
+        // Each header is fine on its own, but they share a guard.
+        assert_eq!(check(&["a.h", "b.h"]), 1);
+        assert_eq!(check(&["missing.h"]), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
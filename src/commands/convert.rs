@@ -0,0 +1,36 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! `guardgen convert`: convert the line endings of existing headers.
+
+use super::{FileWriter, read_header};
+use crate::ModifyArgs;
+use guardgen_lib::header::convert_line_endings;
+
+/// Run `convert` and return the exit code.
+///
+/// @pre `--line-ending` was given; `none` converts to the system default.
+pub fn run(args: &ModifyArgs) -> i32 {
+    let Some(line_ending) = args.guard.line_ending else {
+        eprintln!("Error: convert requires --line-ending.");
+        return 1;
+    };
+
+    let mut writer = FileWriter::new(args.write.mode());
+    let mut failed = false;
+    for path in &args.files {
+        let result = read_header(path).and_then(|text| {
+            let converted = convert_line_endings(&text, line_ending.into());
+            if converted != text {
+                writer.modify(path, &text, &converted)?;
+                writer.status(&format!("Converted line endings of '{}'.", path));
+            }
+            Ok(())
+        });
+        if let Err(message) = result {
+            eprintln!("{}", message);
+            failed = true;
+        }
+    }
+    writer.finish(failed)
+}
@@ -0,0 +1,269 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! Line-based unified diffs for previewing file changes.
+
+use std::fmt::Write;
+
+/// Lines of context around each change.
+const CONTEXT: usize = 3;
+
+/// Most inserted plus deleted lines searched for a minimal diff.
+const MAX_EDIT_DISTANCE: usize = 1000;
+
+/// One line of an edit script.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Edit {
+    Keep(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Render the unified diff turning `old` into `new`.
+///
+/// `old` is `None` for a file that does not exist yet. Line terminators are
+/// part of the compared lines, so line-ending conversions show up as changes.
+///
+/// @post The result is empty exactly when `old == Some(new)`.
+pub fn unified_diff(path: &str, old: Option<&str>, new: &str) -> String {
+    if old == Some(new) {
+        return String::new();
+    }
+    let old_lines: Vec<&str> = old.unwrap_or_default().split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let edits = edit_script(&old_lines, &new_lines);
+
+    let mut out = String::new();
+    match old {
+        Some(_) => writeln!(out, "--- a/{}", path),
+        None => writeln!(out, "--- /dev/null"),
+    }
+    .and_then(|()| writeln!(out, "+++ b/{}", path))
+    .expect("writing to a String cannot fail");
+
+    for hunk in hunks(&edits) {
+        write_hunk(&mut out, &edits[hunk], &old_lines, &new_lines);
+    }
+    out
+}
+
+/// Edit script between `old` and `new`, minimal unless the changed region
+/// needs more than `MAX_EDIT_DISTANCE` edits; then it is replaced as a whole.
+fn edit_script(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    // Common prefix and suffix need no search.
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (a, b) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+    let middle = shortest_edit_script(a, b).unwrap_or_else(|| {
+        (0..a.len())
+            .map(Edit::Delete)
+            .chain((0..b.len()).map(Edit::Insert))
+            .collect()
+    });
+
+    let mut edits: Vec<Edit> = (0..prefix).map(|i| Edit::Keep(i, i)).collect();
+    edits.extend(middle.into_iter().map(|edit| match edit {
+        Edit::Keep(i, j) => Edit::Keep(prefix + i, prefix + j),
+        Edit::Delete(i) => Edit::Delete(prefix + i),
+        Edit::Insert(j) => Edit::Insert(prefix + j),
+    }));
+    edits.extend((0..suffix).map(|k| Edit::Keep(old.len() - suffix + k, new.len() - suffix + k)));
+    edits
+}
+
+/// Shortest edit script between `a` and `b` by Myers' O((N+M)D) algorithm,
+/// or `None` if it has more than `MAX_EDIT_DISTANCE` insertions and deletions.
+///
+/// Memory is O(N+M) for the search plus O(D²) for the backtracking trace.
+fn shortest_edit_script(a: &[&str], b: &[&str]) -> Option<Vec<Edit>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (a.len() + b.len()).min(MAX_EDIT_DISTANCE) as isize;
+    // v[k + offset]: the furthest x reached on diagonal k = x - y.
+    let offset = max + 1;
+    let index = |k: isize| (k + offset) as usize;
+    let mut v = vec![0isize; index(offset) + 1];
+    // trace[d]: diagonals -d-1..=d+1 of v before step d.
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    for d in 0..=max {
+        trace.push(v[index(-d - 1)..=index(d + 1)].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) {
+                v[index(k + 1)]
+            } else {
+                v[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index(k)] = x;
+            if x >= n && y >= m {
+                return Some(backtrack(&trace, n, m));
+            }
+        }
+    }
+    None
+}
+
+/// Walk `trace` back from `(x, y)` to the start, collecting the edits in order.
+fn backtrack(trace: &[Vec<isize>], mut x: isize, mut y: isize) -> Vec<Edit> {
+    let mut edits = Vec::new();
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let at = |k: isize| v[(k + d + 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let (prev_x, prev_y) = (at(prev_k), at(prev_k) - prev_k);
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Keep(x as usize, y as usize));
+        }
+        if d > 0 {
+            edits.push(if x == prev_x {
+                Edit::Insert(prev_y as usize)
+            } else {
+                Edit::Delete(prev_x as usize)
+            });
+        }
+        (x, y) = (prev_x, prev_y);
+    }
+    edits.reverse();
+    edits
+}
+
+/// Ranges of `edits` forming hunks: changes plus up to `CONTEXT` kept lines around them.
+fn hunks(edits: &[Edit]) -> Vec<std::ops::Range<usize>> {
+    let mut ranges: Vec<std::ops::Range<usize>> = Vec::new();
+    for (k, edit) in edits.iter().enumerate() {
+        if matches!(edit, Edit::Keep(..)) {
+            continue;
+        }
+        let start = k.saturating_sub(CONTEXT);
+        let end = (k + 1 + CONTEXT).min(edits.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.end => last.end = end,
+            _ => ranges.push(start..end),
+        }
+    }
+    ranges
+}
+
+fn write_hunk(out: &mut String, edits: &[Edit], old: &[&str], new: &[&str]) {
+    let old_count = edits
+        .iter()
+        .filter(|e| !matches!(e, Edit::Insert(_)))
+        .count();
+    let new_count = edits
+        .iter()
+        .filter(|e| !matches!(e, Edit::Delete(_)))
+        .count();
+    // Start lines are 1-based; a side without lines (an empty file) starts at 0.
+    let old_start = edits.iter().find_map(|e| match e {
+        Edit::Keep(i, _) | Edit::Delete(i) => Some(i + 1),
+        Edit::Insert(_) => None,
+    });
+    let new_start = edits.iter().find_map(|e| match e {
+        Edit::Keep(_, j) | Edit::Insert(j) => Some(j + 1),
+        Edit::Delete(_) => None,
+    });
+    writeln!(
+        out,
+        "@@ -{},{} +{},{} @@",
+        old_start.unwrap_or(0),
+        old_count,
+        new_start.unwrap_or(0),
+        new_count
+    )
+    .expect("writing to a String cannot fail");
+
+    for edit in edits {
+        let (marker, line) = match *edit {
+            Edit::Keep(i, _) => (' ', old[i]),
+            Edit::Delete(i) => ('-', old[i]),
+            Edit::Insert(j) => ('+', new[j]),
+        };
+        out.push(marker);
+        out.push_str(line);
+        if !line.ends_with('\n') {
+            out.push_str("\n\\ No newline at end of file\n");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_text_has_no_diff() {
+        assert_eq!(unified_diff("a.h", Some("x\n"), "x\n"), "");
+    }
+
+    #[test]
+    fn new_file_is_all_insertions() {
+        assert_eq!(
+            unified_diff("a.h", None, "one\ntwo\n"),
+            "--- /dev/null\n+++ b/a.h\n@@ -0,0 +1,2 @@\n+one\n+two\n"
+        );
+    }
+
+    #[test]
+    fn changes_are_shown_with_context() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n";
+        let new = "0\n1\n2\n3\n4\n5\n6\n7\nEIGHT\n9";
+        assert_eq!(
+            unified_diff("a.h", Some(old), new),
+            "--- a/a.h\n+++ b/a.h\n\
+             @@ -1,3 +1,4 @@\n+0\n 1\n 2\n 3\n\
+             @@ -5,5 +6,5 @@\n 5\n 6\n 7\n-8\n-9\n+EIGHT\n+9\n\\ No newline at end of file\n"
+        );
+    }
+
+    #[test]
+    fn edit_scripts_are_minimal() {
+        let old = ["a\n", "b\n", "c\n", "a\n", "b\n", "b\n", "a\n"];
+        let new = ["c\n", "b\n", "a\n", "b\n", "a\n", "c\n"];
+        let edits = edit_script(&old, &new);
+        let changes = edits
+            .iter()
+            .filter(|e| !matches!(e, Edit::Keep(..)))
+            .count();
+        assert_eq!(changes, 5);
+        let kept: Vec<&str> = edits
+            .iter()
+            .filter_map(|e| match *e {
+                Edit::Keep(i, j) => {
+                    assert_eq!(old[i], new[j]);
+                    Some(old[i])
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(kept.len(), 4);
+    }
+
+    #[test]
+    fn large_rewrites_become_one_replacement() {
+        let old = "x\n".repeat(MAX_EDIT_DISTANCE);
+        let new = "x\r\n".repeat(MAX_EDIT_DISTANCE);
+        let diff = unified_diff("a.h", Some(&old), &new);
+        let header = format!("@@ -1,{0} +1,{0} @@\n", MAX_EDIT_DISTANCE);
+        assert!(diff.starts_with(&format!("--- a/a.h\n+++ b/a.h\n{}-x\n", header)));
+        assert_eq!(diff.matches("@@ -").count(), 1);
+        assert!(diff.ends_with("+x\r\n"));
+    }
+}
@@ -0,0 +1,355 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! `guardgen fix`: repair the problems `check` reports where that is safe.
+
+use super::{FileWriter, line_ending_for, read_header};
+use crate::ModifyArgs;
+use guardgen_lib::IncludeGuardGenerator;
+use guardgen_lib::header::{
+    CheckOptions, GuardNameParts, HeaderInfo, HeaderIssue, add_guard, add_linkage, check_header,
+    convert_line_endings, rename_guard,
+};
+use guardgen_lib::spdx::{SpdxHeader, insert_missing};
+use std::collections::{HashMap, HashSet};
+
+/// Settings shared by all files of a run.
+struct Settings {
+    options: CheckOptions,
+    spdx: Option<SpdxHeader>,
+    languages: HashMap<String, crate::Language>,
+}
+
+/// Run `fix` and return the exit code.
+///
+/// Missing guards are added, guards that are not UUID-based, do not match the
+/// requested prefix, suffix or UUID version, or duplicate the guard of an
+/// earlier file are renamed, guarded C headers (given with `-x c` or inferred
+/// per file) get their missing linkage block, missing SPDX lines are added
+/// when SPDX options are set, and line endings are converted. Structural
+/// problems are reported on stderr and make the exit code non-zero.
+pub fn run(args: &ModifyArgs) -> i32 {
+    let (mut generator, settings) = match prepare(args) {
+        Ok(prepared) => prepared,
+        Err(message) => {
+            eprintln!("{}", message);
+            return 1;
+        }
+    };
+
+    let mut writer = FileWriter::new(args.write.mode());
+    let mut failed = false;
+    let mut seen = HashSet::new();
+    for path in &args.files {
+        match fix_file(
+            args,
+            &mut generator,
+            &mut writer,
+            &mut seen,
+            &settings,
+            path,
+        ) {
+            Ok(true) => {}
+            Ok(false) => failed = true,
+            Err(message) => {
+                eprintln!("{}", message);
+                failed = true;
+            }
+        }
+    }
+    writer.finish(failed)
+}
+
+/// Build the generator and the settings of a run.
+fn prepare(args: &ModifyArgs) -> Result<(IncludeGuardGenerator, Settings), String> {
+    let generator = args.guard.generator(args.write.mode())?;
+    let config = args.guard.config()?;
+    let spdx = args.spdx.resolve(config.spdx, &args.guard)?;
+    let options = args
+        .guard
+        .check_options(args.guard.linkage(config.linkage)?);
+    let settings = Settings {
+        options,
+        spdx,
+        languages: config.languages,
+    };
+    Ok((generator, settings))
+}
+
+/// Fix one file; `Ok(false)` means problems were left that need manual attention.
+fn fix_file(
+    args: &ModifyArgs,
+    generator: &mut IncludeGuardGenerator,
+    writer: &mut FileWriter,
+    seen: &mut HashSet<String>,
+    settings: &Settings,
+    path: &str,
+) -> Result<bool, String> {
+    let guard_args = &args.guard;
+    let original = read_header(path)?;
+    let language = guard_args.language_for(path, Some(&original), &settings.languages);
+    // Without `-x`, the linkage checks follow the language inferred for this file.
+    let options = &CheckOptions {
+        language: Some(language),
+        ..settings.options.clone()
+    };
+    let info = HeaderInfo::analyze(&original);
+    let duplicate = info
+        .guard
+        .as_ref()
+        .is_some_and(|guard| !seen.insert(guard.name.clone()));
+
+    let mut text = original.clone();
+    let mut rename = duplicate;
+    let mut fixed = true;
+    let mut added = false;
+    let mut link = None;
+    for issue in check_header(&original, options) {
+        match issue {
+            HeaderIssue::MissingGuard if !info.pragma_once => {
+                let mut guard = generator
+                    .try_next_guard(
+                        guard_args.prefix(),
+                        guard_args.suffix.as_deref(),
+                        language,
+                        line_ending_for(&text, guard_args.line_ending.map(Into::into)),
+                        guard_args.uuid_kind(),
+                    )
+                    .map_err(|e| guard_args.generation_error(e))?;
+                guard.set_linkage(options.linkage.clone());
+                seen.insert(guard.as_str().to_string());
+                text = add_guard(&text, &guard);
+                added = true;
+                writer.status(&format!("{}: added guard '{}'", path, guard.as_str()));
+            }
+            HeaderIssue::NotUuidGuard { .. }
+            | HeaderIssue::PrefixMismatch { .. }
+            | HeaderIssue::SuffixMismatch { .. }
+            | HeaderIssue::UuidVersionMismatch { .. } => rename = true,
+            // A guard added for a language with C linkage brings its linkage block along.
+            HeaderIssue::MissingLinkageBlock | HeaderIssue::FunctionWithoutLinkage { .. }
+                if added && language.has_c_linkage() => {}
+            // Added after renaming, which relies on the original line numbers.
+            HeaderIssue::MissingLinkageBlock | HeaderIssue::FunctionWithoutLinkage { .. }
+                if info.guard.is_some() =>
+            {
+                link = Some(issue)
+            }
+            HeaderIssue::LineEndingMismatch { .. } => {}
+            issue => {
+                eprintln!("{}: {} (not fixed)", path, issue);
+                fixed = false;
+            }
+        }
+    }
+
+    if let (true, Some(existing)) = (rename, &info.guard) {
+        // Keep the parts of the old name that no option overrides.
+        let parts = GuardNameParts::parse(&existing.name);
+        let prefix = match (&guard_args.prefix, &parts) {
+            (Some(prefix), _) => prefix.clone(),
+            (None, Some(parts)) => parts.prefix.clone(),
+            (None, None) => guard_args.prefix().to_string(),
+        };
+        let suffix = match (&guard_args.suffix, &parts) {
+            (Some(suffix), _) => Some(suffix.clone()),
+            (None, Some(parts)) => parts.suffix.clone(),
+            (None, None) => None,
+        };
+        let guard = generator
+            .try_next_guard(
+                &prefix,
+                suffix.as_deref(),
+                language,
+                guard_args.line_ending(),
+                guard_args.uuid_kind(),
+            )
+            .map_err(|e| guard_args.generation_error(e))?;
+        seen.insert(guard.as_str().to_string());
+        text = rename_guard(&text, existing, guard.as_str());
+        writer.status(&format!(
+            "{}: renamed guard '{}' to '{}'",
+            path,
+            existing.name,
+            guard.as_str()
+        ));
+    }
+
+    if let Some(issue) = link {
+        match add_linkage(&text, &options.linkage) {
+            Some(linked) => {
+                text = linked;
+                writer.status(&format!("{}: added linkage block", path));
+            }
+            None => {
+                eprintln!("{}: {} (not fixed)", path, issue);
+                fixed = false;
+            }
+        }
+    }
+
+    if let Some(spdx) = &settings.spdx {
+        let with_spdx = insert_missing(&text, spdx, language);
+        if with_spdx != text {
+            text = with_spdx;
+            writer.status(&format!("{}: added SPDX lines", path));
+        }
+    }
+
+    if let Some(line_ending) = guard_args.line_ending.map(Into::into) {
+        let converted = convert_line_endings(&text, line_ending);
+        if converted != text {
+            text = converted;
+            writer.status(&format!("{}: converted line endings", path));
+        }
+    }
+
+    writer.modify(path, &original, &text)?;
+    Ok(fixed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Command;
+    use crate::commands::WriteMode;
+    use crate::commands::tests::{parse_command, scratch_dir};
+    use std::fs;
+    use std::path::Path;
+
+    const GUARD: &str = "ACME_0190F4C3_1A2B_7C3D_8E4F_5A6B7C8D9E0F_H";
+
+    /// Run `fix_file` over `files` in `dir` with the options `argv`, like `run`
+    /// does, and return each file's result and new content.
+    fn fix(
+        dir: &Path,
+        argv: &[&str],
+        files: &[(&str, &str)],
+    ) -> Vec<(Result<bool, String>, String)> {
+        let paths: Vec<String> = files
+            .iter()
+            .map(|(name, text)| {
+                let path = dir.join(name);
+                fs::write(&path, text).unwrap();
+                path.to_str().unwrap().to_string()
+            })
+            .collect();
+        let mut command = vec!["fix"];
+        command.extend(argv);
+        command.extend(paths.iter().map(String::as_str));
+        let Command::Fix(args) = parse_command(dir, &command) else {
+            unreachable!("parsed as fix");
+        };
+        let (mut generator, settings) = prepare(&args).unwrap();
+        let mut writer = FileWriter::new(WriteMode::Write);
+        writer.journal_dir = dir.join("journal");
+        let mut seen = HashSet::new();
+        paths
+            .iter()
+            .map(|path| {
+                let result = fix_file(
+                    &args,
+                    &mut generator,
+                    &mut writer,
+                    &mut seen,
+                    &settings,
+                    path,
+                );
+                (result, fs::read_to_string(path).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn adds_missing_guards_but_not_next_to_pragma_once() {
+        let dir = scratch_dir("fix-add");
+        let fixed = fix(
+            &dir,
+            &["-x", "cxx"],
+            &[
+                ("a.h", "int f();\n"),
+                ("once.h", "#pragma once\nint g();\n"),
+            ],
+        );
+
+        assert_eq!(fixed[0].0, Ok(true));
+        assert!(fixed[0].1.starts_with("#ifndef UUID_"));
+        assert!(fixed[0].1.contains("int f();"));
+        // A #pragma once header is reported, not given a second guard.
+        assert_eq!(fixed[1].0, Ok(false));
+        assert_eq!(fixed[1].1, "#pragma once\nint g();\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn renames_legacy_mismatched_and_duplicate_guards() {
+        let dir = scratch_dir("fix-rename");
+        let guarded = format!(
+            "#ifndef {0}\n#define {0}\nint f();\n#endif /* {0} */\n",
+            GUARD
+        );
+        let fixed = fix(
+            &dir,
+            &["-x", "cxx", "--suffix", "H"],
+            &[
+                ("legacy.h", "#ifndef LEGACY_H\n#define LEGACY_H\n#endif\n"),
+                ("first.h", &guarded),
+                ("copy.h", &guarded),
+                ("other.h", &guarded.replace("_H", "_HPP")),
+            ],
+        );
+
+        assert!(fixed.iter().all(|(result, _)| *result == Ok(true)));
+        assert!(fixed[0].1.starts_with("#ifndef UUID_"));
+        assert!(!fixed[0].1.contains("LEGACY_H"));
+        // The first file keeps its guard; the copy gets a fresh one with the same prefix.
+        assert_eq!(fixed[1].1, guarded);
+        assert!(fixed[2].1.starts_with("#ifndef ACME_"));
+        assert!(!fixed[2].1.contains(GUARD));
+        // The suffix option overrides the old suffix.
+        assert!(!fixed[3].1.contains("_HPP"));
+        assert!(fixed[3].1.starts_with("#ifndef ACME_"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn links_headers_inferred_as_c() {
+        let dir = scratch_dir("fix-link-inferred");
+        let guarded = |body: &str| format!("#ifndef {0}\n#define {0}\n{1}#endif\n", GUARD, body);
+        let c = guarded("int f(void);\n");
+        let cxx = guarded("class S {};\nint f(void);\n");
+        let fixed = fix(&dir, &[], &[("c.h", &c)]);
+        let fixed_cxx = fix(&dir, &[], &[("cxx.h", &cxx)]);
+
+        assert_eq!(fixed[0].0, Ok(true));
+        assert!(fixed[0].1.contains("extern \"C\" {"));
+        // A `.h` using C++-only constructs is C++ and needs no linkage block.
+        assert_eq!(fixed_cxx[0], (Ok(true), cxx));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn links_c_headers_and_leaves_structural_problems() {
+        let dir = scratch_dir("fix-link");
+        let guarded = format!("#ifndef {0}\n#define {0}\nint f(void);\n#endif\n", GUARD);
+        let fixed = fix(
+            &dir,
+            &["-x", "c"],
+            &[
+                ("c.h", &guarded),
+                (
+                    "open.h",
+                    &format!("#ifndef {0}\n#define {0}\nint f(void);\n", GUARD),
+                ),
+            ],
+        );
+
+        assert_eq!(fixed[0].0, Ok(true));
+        assert!(fixed[0].1.contains("extern \"C\" {"));
+        assert!(fixed[0].1.starts_with(&format!("#ifndef {}", GUARD)));
+        // An unterminated guard needs a human; nothing is written.
+        assert_eq!(fixed[1].0, Ok(false));
+        assert!(!fixed[1].1.contains("extern"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,63 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! `guardgen inspect`: describe the guard of existing headers.
+
+use super::{format_utc, read_header};
+use crate::FilesArgs;
+use guardgen_lib::UuidKind;
+use guardgen_lib::header::{GuardNameParts, HeaderInfo};
+
+/// Run `inspect` and return the exit code.
+pub fn run(args: &FilesArgs) -> i32 {
+    let mut code = 0;
+    for (i, path) in args.files.iter().enumerate() {
+        let text = match read_header(path) {
+            Ok(text) => text,
+            Err(message) => {
+                eprintln!("{}", message);
+                code = 1;
+                continue;
+            }
+        };
+        if i > 0 {
+            println!();
+        }
+        print_info(path, &HeaderInfo::analyze(&text));
+    }
+    code
+}
+
+fn print_info(path: &str, info: &HeaderInfo) {
+    println!("{}:", path);
+    match &info.guard {
+        None => println!("  guard:         (none)"),
+        Some(guard) => {
+            println!("  guard:         {}", guard.name);
+            match GuardNameParts::parse(&guard.name) {
+                None => println!("  uuid:          (none)"),
+                Some(parts) => {
+                    println!("  prefix:        {}", parts.prefix);
+                    println!("  uuid:          {}", parts.uuid);
+                    let version = match parts.uuid_kind {
+                        Some(UuidKind::V4) => "v4",
+                        Some(UuidKind::V7) => "v7",
+                        None => "other",
+                    };
+                    println!("  version:       {}", version);
+                    if let Some(millis) = parts.unix_millis {
+                        println!("  timestamp:     {}", format_utc(millis));
+                    }
+                    println!(
+                        "  suffix:        {}",
+                        parts.suffix.as_deref().unwrap_or("(none)")
+                    );
+                }
+            }
+        }
+    }
+    let yes_no = |flag: bool| if flag { "yes" } else { "no" };
+    println!("  linkage block: {}", yes_no(info.has_linkage_block));
+    println!("  pragma once:   {}", yes_no(info.pragma_once));
+    println!("  line endings:  {:?}", info.line_endings);
+}
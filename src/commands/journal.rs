@@ -0,0 +1,232 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! Backup journal of the files a run rewrote, so `guardgen undo` can restore them.
+//!
+//! Each run that writes files gets a directory `<run-id>/` in the journal
+//! directory (see `default_dir`), which does not depend on the working
+//! directory, so `undo` works from anywhere. For the n-th file it holds
+//! `<n>.orig` (the content before the run, absent for a file the run created)
+//! and `<n>.new` (the content the run wrote), and the `manifest` lists
+//! `<n>\t<absolute path>` per line. Replacements are recorded before the file
+//! is replaced and creations right after the file was created, so a crash can
+//! only leave entries whose file is unchanged or missing.
+
+use super::format_utc;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+const MANIFEST: &str = "manifest";
+/// Present in a run directory once the run has been undone.
+const UNDONE: &str = "undone";
+
+/// The journal of the current run.
+pub struct Journal {
+    run_id: String,
+    dir: PathBuf,
+    entries: usize,
+}
+
+impl Journal {
+    /// Start the journal of a new run in the journal directory `root`.
+    pub fn start(root: &Path) -> io::Result<Self> {
+        let millis = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        // 2026-10-18T20:05:12.345Z -> 20261018T200512345Z, sortable by time.
+        let stamp: String = format_utc(millis)
+            .chars()
+            .filter(|c| !matches!(c, '-' | ':' | '.'))
+            .collect();
+        let run_id = format!("{}-{}", stamp, std::process::id());
+        let dir = root.join(&run_id);
+        fs::create_dir_all(&dir)?;
+        Ok(Journal {
+            run_id,
+            dir,
+            entries: 0,
+        })
+    }
+
+    /// The identifier `guardgen undo` accepts for this run.
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    /// Record that `path` changes from `old` to `new`; `old` is `None` for a
+    /// file the run creates.
+    ///
+    /// @pre The parent directory of `path` exists.
+    pub fn record(&mut self, path: &Path, old: Option<&[u8]>, new: &[u8]) -> io::Result<()> {
+        let path = absolute_path(path)?;
+        let path = path
+            .to_str()
+            .filter(|p| !p.contains(['\t', '\n']))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "path cannot be recorded in the journal",
+                )
+            })?;
+
+        let index = self.entries;
+        if let Some(old) = old {
+            fs::write(self.dir.join(format!("{}.orig", index)), old)?;
+        }
+        fs::write(self.dir.join(format!("{}.new", index)), new)?;
+        let mut manifest = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(MANIFEST))?;
+        writeln!(manifest, "{}\t{}", index, path)?;
+        manifest.sync_all()?;
+        self.entries += 1;
+        Ok(())
+    }
+}
+
+/// The absolute path of `path`, which need not exist, with symlinks in its
+/// directory resolved.
+fn absolute_path(path: &Path) -> io::Result<PathBuf> {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => {
+            let parent = if parent.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                parent
+            };
+            Ok(fs::canonicalize(parent)?.join(name))
+        }
+        _ => fs::canonicalize(path),
+    }
+}
+
+/// One file recorded by a past run.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub path: PathBuf,
+    /// Content before the run; `None` if the run created the file.
+    pub original: Option<Vec<u8>>,
+    pub written: Vec<u8>,
+}
+
+/// A run found in the journal directory.
+pub struct Run {
+    pub run_id: String,
+    dir: PathBuf,
+}
+
+impl Run {
+    /// Whether the run has already been undone.
+    pub fn is_undone(&self) -> bool {
+        self.dir.join(UNDONE).exists()
+    }
+
+    /// Mark the run as undone.
+    pub fn mark_undone(&self) -> io::Result<()> {
+        fs::write(self.dir.join(UNDONE), "")
+    }
+
+    /// The files the run replaced, in the order it replaced them.
+    pub fn entries(&self) -> io::Result<Vec<Entry>> {
+        let manifest = fs::read_to_string(self.dir.join(MANIFEST))?;
+        manifest
+            .lines()
+            .map(|line| {
+                let (index, path) = line.split_once('\t').ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "malformed journal manifest")
+                })?;
+                let original = match fs::read(self.dir.join(format!("{}.orig", index))) {
+                    Ok(original) => Some(original),
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+                    Err(e) => return Err(e),
+                };
+                Ok(Entry {
+                    path: PathBuf::from(path),
+                    original,
+                    written: fs::read(self.dir.join(format!("{}.new", index)))?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// The journal directory: `guardgen/journal` in the user's state directory,
+/// i.e. `$XDG_STATE_HOME`, `~/.local/state` or `%LOCALAPPDATA%`.
+///
+/// Only when none of these is known does it fall back to `.guardgen/journal`
+/// in the working directory.
+pub fn default_dir() -> PathBuf {
+    let non_empty = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+    let state = non_empty("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| non_empty("HOME").map(|home| Path::new(&home).join(".local/state")))
+        .or_else(|| non_empty("LOCALAPPDATA").map(PathBuf::from));
+    match state {
+        Some(state) => state.join("guardgen").join("journal"),
+        None => PathBuf::from(".guardgen/journal"),
+    }
+}
+
+/// All runs journaled in `root`, oldest first.
+pub fn runs(root: &Path) -> io::Result<Vec<Run>> {
+    let mut runs = match fs::read_dir(root) {
+        Ok(dir) => dir
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().join(MANIFEST).is_file())
+            .filter_map(|entry| {
+                Some(Run {
+                    run_id: entry.file_name().into_string().ok()?,
+                    dir: entry.path(),
+                })
+            })
+            .collect(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    runs.sort_by(|a, b| a.run_id.cmp(&b.run_id));
+    Ok(runs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::scratch_dir;
+
+    #[test]
+    fn manifest_round_trips_replaced_and_created_files() {
+        let dir = scratch_dir("journal");
+        let root = dir.join("journal");
+        let replaced = dir.join("a.h");
+        fs::write(&replaced, "old").unwrap();
+
+        let mut journal = Journal::start(&root).unwrap();
+        journal.record(&replaced, Some(b"old"), b"new").unwrap();
+        journal.record(&dir.join("b.h"), None, b"created").unwrap();
+
+        let runs = runs(&root).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].run_id, journal.run_id());
+        assert!(!runs[0].is_undone());
+        let dir = fs::canonicalize(&dir).unwrap();
+        assert_eq!(
+            runs[0].entries().unwrap(),
+            [
+                Entry {
+                    path: dir.join("a.h"),
+                    original: Some(b"old".to_vec()),
+                    written: b"new".to_vec(),
+                },
+                Entry {
+                    path: dir.join("b.h"),
+                    original: None,
+                    written: b"created".to_vec(),
+                },
+            ]
+        );
+        runs[0].mark_undone().unwrap();
+        assert!(runs[0].is_undone());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
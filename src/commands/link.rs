@@ -0,0 +1,52 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! `guardgen link`: add the C linkage block to headers that are already guarded.
+
+use super::{FileWriter, read_header};
+use crate::ModifyArgs;
+use guardgen_lib::header::{HeaderInfo, add_linkage};
+
+/// Run `link` and return the exit code.
+///
+/// The block opens after the last `#include` inside the guard and closes
+/// before its `#endif`. Headers without a complete guard are reported as
+/// failures; headers that already have a linkage block are left untouched.
+pub fn run(args: &ModifyArgs) -> i32 {
+    let linkage = match args
+        .guard
+        .config()
+        .and_then(|config| args.guard.linkage(config.linkage))
+    {
+        Ok(linkage) => linkage,
+        Err(message) => {
+            eprintln!("{}", message);
+            return 1;
+        }
+    };
+
+    let mut writer = FileWriter::new(args.write.mode());
+    let mut failed = false;
+    for path in &args.files {
+        let result = read_header(path).and_then(|text| {
+            let Some(linked) = add_linkage(&text, &linkage) else {
+                let guarded = HeaderInfo::analyze(&text)
+                    .guard
+                    .is_some_and(|g| g.define_line.is_some() && g.endif_line.is_some());
+                if !guarded {
+                    return Err(format!("Error: '{}' has no complete include guard.", path));
+                }
+                writer.status(&format!("Skipped '{}': already has a linkage block.", path));
+                return Ok(());
+            };
+            writer.modify(path, &text, &linked)?;
+            writer.status(&format!("Linkage block added to '{}'.", path));
+            Ok(())
+        });
+        if let Err(message) = result {
+            eprintln!("{}", message);
+            failed = true;
+        }
+    }
+    writer.finish(failed)
+}
@@ -0,0 +1,176 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! `guardgen new`: generate guards and print them or write them to new files.
+
+use super::FileWriter;
+use crate::NewArgs;
+use crate::config::TemplateConfig;
+use guardgen_lib::doxygen::file_block;
+use guardgen_lib::spdx::insert_missing;
+use guardgen_lib::template::{Template, TemplateValues};
+use std::fs;
+use std::path::Path;
+
+/// Run `new` and return the exit code.
+///
+/// All files share one generator, so v7 guards are ordered in the order the
+/// files were given. A failure for one file does not stop the others; with
+/// more than one file a summary line follows, and the exit code is 1 if any
+/// file failed. With `--dry-run`/`--check` the files are only previewed.
+pub fn run(args: &NewArgs) -> i32 {
+    let guard_args = &args.guard;
+    let prepared = guard_args
+        .generator(args.write.mode())
+        .and_then(|generator| {
+            let config = guard_args.config()?;
+            let spdx = args.spdx.resolve(config.spdx, guard_args)?;
+            let linkage = guard_args.linkage(config.linkage)?;
+            let skeleton = skeleton(args, config.template)?;
+            Ok((generator, skeleton, spdx, linkage, config.languages))
+        });
+    let (mut generator, skeleton, spdx, linkage, languages) = match prepared {
+        Ok(prepared) => prepared,
+        Err(message) => {
+            eprintln!("{}", message);
+            return 1;
+        }
+    };
+    let mut generate = |file_path: &str| -> Result<String, String> {
+        let language = guard_args.language_for(file_path, None, &languages);
+        let mut guard = generator
+            .try_next_guard(
+                guard_args.prefix(),
+                guard_args.suffix.as_deref(),
+                language,
+                guard_args.line_ending(),
+                guard_args.uuid_kind(),
+            )
+            .map_err(|e| guard_args.generation_error(e))?;
+        guard.set_linkage(linkage.clone());
+        guard.set_assembler_safe(args.assembler_safe);
+        guard.set_strict(args.strict);
+        guard.set_enforcement(args.enforce.resolve(language)?);
+        guard.set_alias(args.alias.resolve(file_path)?);
+        if let Some(hiding) = args.doxygen.hide_guard {
+            guard.set_hiding(hiding.into());
+        }
+        let file_name = Path::new(file_path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let namespace = args.namespace.resolve(file_path, language)?;
+        let style = args.namespace.style();
+
+        let mut header = match &skeleton {
+            None => guard.wrap(&namespace.wrap("", style, guard.newline())),
+            Some((template, _)) if !namespace.is_global() && !template.has_namespace() => {
+                return Err(
+                    "Error: The template has no {{namespace_open}}/{{namespace_close}} placeholders."
+                        .to_string(),
+                );
+            }
+            Some((template, values)) => {
+                let values = TemplateValues {
+                    file_name: file_name.clone(),
+                    namespace,
+                    namespace_style: style,
+                    ..values.clone()
+                };
+                template.render(&guard, &values)
+            }
+        };
+        if args.doxygen.file_block() {
+            let block = file_block(&file_name, args.doxygen.brief.as_deref(), guard.newline());
+            header = block + guard.newline() + &header;
+        }
+        // SPDX lines go above the guard, unless the template already has them.
+        Ok(match &spdx {
+            Some(spdx) => insert_missing(&header, spdx, language),
+            None => header,
+        })
+    };
+
+    let outputs: Vec<&str> = args.outputs().collect();
+    if outputs.is_empty() {
+        // Print the include guard to stdout if no output file is specified.
+        return match generate("") {
+            Ok(header) if skeleton.is_some() => {
+                print!("{}", header);
+                0
+            }
+            Ok(guard) => {
+                println!("{}", guard);
+                0
+            }
+            Err(message) => {
+                eprintln!("{}", message);
+                1
+            }
+        };
+    }
+
+    let mut writer = FileWriter::new(args.write.mode());
+    let mut failed = 0;
+    for file_path in &outputs {
+        let result = generate(file_path)
+            .and_then(|guard| writer.create(file_path, &guard, args.overwrite, args.parents));
+        match result {
+            Ok(()) => writer.status(&format!("Guard written to '{}'.", file_path)),
+            Err(message) => {
+                eprintln!("{}", message);
+                failed += 1;
+            }
+        }
+    }
+    if outputs.len() > 1 {
+        writer.status(&format!(
+            "{} of {} file(s) written, {} failed.",
+            outputs.len() - failed,
+            outputs.len(),
+            failed
+        ));
+    }
+    writer.finish(failed > 0)
+}
+
+/// The template and placeholder values selected by the options and the config, if any.
+///
+/// `file_name` is left empty; it differs per header.
+fn skeleton(
+    args: &NewArgs,
+    config: TemplateConfig,
+) -> Result<Option<(Template, TemplateValues)>, String> {
+    let path = match (&args.template.template, config.path) {
+        (Some(path), _) => path.into(),
+        (None, Some(path)) => path,
+        (None, None) => return Ok(None),
+    };
+    let text = fs::read_to_string(&path)
+        .map_err(|e| format!("Error reading template '{}': {}", path.display(), e))?;
+    let template = Template::parse(&text)
+        .map_err(|e| format!("Error in template '{}': {}", path.display(), e))?;
+
+    let values = TemplateValues {
+        file_name: String::new(),
+        date: {
+            let (year, month, day) = args.guard.today()?;
+            format!("{:04}-{:02}-{:02}", year, month, day)
+        },
+        author: args
+            .template
+            .author
+            .clone()
+            .or(config.author)
+            .unwrap_or_default(),
+        project: args
+            .template
+            .project
+            .clone()
+            .or(config.project)
+            .unwrap_or_default(),
+        ..TemplateValues::default()
+    };
+    Ok(Some((template, values)))
+}
@@ -0,0 +1,191 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! `guardgen undo`: restore the files a previous run rewrote.
+
+use super::journal::{self, Run};
+use super::{io_error, replace_file};
+use crate::UndoArgs;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Run `undo` and return the exit code.
+///
+/// Without a run id, the most recent run that was not undone yet is restored.
+/// Files the run created are removed again. A file whose content differs from
+/// what the run wrote is left alone, and the run stays undoable so it can be
+/// retried after resolving that.
+pub fn run(args: &UndoArgs) -> i32 {
+    undo(args, &journal::default_dir())
+}
+
+/// `run` with the journal directory `root`.
+fn undo(args: &UndoArgs, root: &Path) -> i32 {
+    let runs = match journal::runs(root) {
+        Ok(runs) => runs,
+        Err(e) => {
+            eprintln!(
+                "Error reading the backup journal '{}': {}",
+                root.display(),
+                e
+            );
+            return 1;
+        }
+    };
+
+    if args.list {
+        for line in list(&runs) {
+            println!("{}", line);
+        }
+        return 0;
+    }
+
+    let run = match &args.run_id {
+        Some(run_id) => runs.iter().find(|run| run.run_id == *run_id),
+        None => runs.iter().rev().find(|run| !run.is_undone()),
+    };
+    let Some(run) = run else {
+        match &args.run_id {
+            Some(run_id) => eprintln!("Error: No run '{}' in '{}'.", run_id, root.display()),
+            None => eprintln!("Error: No run to undo in '{}'.", root.display()),
+        }
+        return 1;
+    };
+    if run.is_undone() {
+        eprintln!("Error: Run '{}' has already been undone.", run.run_id);
+        return 1;
+    }
+
+    match restore(run) {
+        Ok(true) => match run.mark_undone() {
+            Ok(()) => {
+                println!("Run '{}' undone.", run.run_id);
+                0
+            }
+            Err(e) => {
+                eprintln!("Error marking run '{}' as undone: {}", run.run_id, e);
+                1
+            }
+        },
+        Ok(false) => 1,
+        Err(message) => {
+            eprintln!("{}", message);
+            1
+        }
+    }
+}
+
+/// The lines of `undo --list`: one run id per run, oldest first.
+fn list(runs: &[Run]) -> Vec<String> {
+    runs.iter()
+        .map(|run| {
+            let state = if run.is_undone() { " (undone)" } else { "" };
+            format!("{}{}", run.run_id, state)
+        })
+        .collect()
+}
+
+/// Restore the files of `run`; `Ok(false)` means some were refused.
+fn restore(run: &Run) -> Result<bool, String> {
+    let entries = run
+        .entries()
+        .map_err(|e| format!("Error reading run '{}': {}", run.run_id, e))?;
+
+    let mut complete = true;
+    // Later entries may build on earlier ones, so unwind in reverse.
+    for entry in entries.iter().rev() {
+        let display = entry.path.display().to_string();
+        let current = match fs::read(&entry.path) {
+            Ok(current) => Some(current),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(io_error(&display, &e, "reading")),
+        };
+        if current == entry.original {
+            // Never written (the run stopped first) or already restored.
+            continue;
+        }
+        if current.as_ref() != Some(&entry.written) {
+            eprintln!(
+                "Error: '{}' has changed since run '{}'; not restored.",
+                display, run.run_id
+            );
+            complete = false;
+            continue;
+        }
+        match &entry.original {
+            Some(original) => {
+                replace_file(&entry.path, original)
+                    .map_err(|e| io_error(&display, &e, "writing to"))?;
+                println!("Restored '{}'.", display);
+            }
+            None => {
+                fs::remove_file(&entry.path).map_err(|e| io_error(&display, &e, "removing"))?;
+                println!("Removed '{}'.", display);
+            }
+        }
+    }
+    Ok(complete)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::scratch_dir;
+    use crate::commands::{FileWriter, WriteMode};
+
+    /// Write `a.h` over an existing file and create `b.h` in one run journaled in `root`.
+    fn journaled_run(dir: &Path, root: &Path) -> (String, String) {
+        let replaced = dir.join("a.h").to_str().unwrap().to_string();
+        let created = dir.join("b.h").to_str().unwrap().to_string();
+        fs::write(&replaced, "old\n").unwrap();
+        let mut writer = FileWriter::new(WriteMode::Write);
+        writer.journal_dir = root.to_path_buf();
+        writer.modify(&replaced, "old\n", "new\n").unwrap();
+        writer.create(&created, "created\n", false, false).unwrap();
+        assert_eq!(writer.finish(false), 0);
+        (replaced, created)
+    }
+
+    fn undo_args(list: bool) -> UndoArgs {
+        UndoArgs { run_id: None, list }
+    }
+
+    #[test]
+    fn restores_replaced_files_and_removes_created_ones() {
+        let dir = scratch_dir("undo");
+        let root = dir.join("journal");
+        let (replaced, created) = journaled_run(&dir, &root);
+
+        assert_eq!(undo(&undo_args(false), &root), 0);
+        assert_eq!(fs::read_to_string(&replaced).unwrap(), "old\n");
+        assert!(!Path::new(&created).exists());
+
+        let runs = journal::runs(&root).unwrap();
+        assert_eq!(list(&runs), [format!("{} (undone)", runs[0].run_id)]);
+        assert_eq!(undo(&undo_args(true), &root), 0);
+        // Nothing is left to undo.
+        assert_eq!(undo(&undo_args(false), &root), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn refuses_files_changed_since_the_run() {
+        let dir = scratch_dir("undo-changed");
+        let root = dir.join("journal");
+        let (replaced, created) = journaled_run(&dir, &root);
+        fs::write(&replaced, "edited\n").unwrap();
+
+        assert_eq!(undo(&undo_args(false), &root), 1);
+        assert_eq!(fs::read_to_string(&replaced).unwrap(), "edited\n");
+        assert!(!Path::new(&created).exists());
+
+        // The run stays undoable and succeeds once the file is back.
+        let runs = journal::runs(&root).unwrap();
+        assert_eq!(list(&runs), [runs[0].run_id.clone()]);
+        fs::write(&replaced, "new\n").unwrap();
+        assert_eq!(undo(&undo_args(false), &root), 0);
+        assert_eq!(fs::read_to_string(&replaced).unwrap(), "old\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
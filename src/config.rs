@@ -0,0 +1,151 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! Project configuration read from `guardgen.toml`.
+//!
+//! The file is looked up in the working directory and its ancestors, unless
+//! `--config` names one. Command-line options take precedence over it, and
+//! relative paths in it are relative to the directory containing it.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name looked up when `--config` is not given.
+pub const CONFIG_FILE: &str = "guardgen.toml";
+
+/// Contents of `guardgen.toml`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub template: TemplateConfig,
+    pub spdx: SpdxConfig,
+    pub linkage: LinkageConfig,
+    /// The `[languages]` table: target language by header extension (without the dot).
+    pub languages: HashMap<String, crate::Language>,
+}
+
+/// The `[template]` table: header skeletons for `new`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TemplateConfig {
+    /// Template file used by `new`.
+    pub path: Option<PathBuf>,
+    /// Value of `{{author}}`.
+    pub author: Option<String>,
+    /// Value of `{{project}}`.
+    pub project: Option<String>,
+}
+
+/// The `[spdx]` table: SPDX lines above the guard.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpdxConfig {
+    /// SPDX license expression.
+    pub license: Option<String>,
+    /// Copyright holder.
+    pub holder: Option<String>,
+    /// Copyright year (or range).
+    pub year: Option<String>,
+}
+
+/// The `[linkage]` table: macro pair used instead of the `extern "C"` block.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LinkageConfig {
+    /// Macro opening the block, e.g. `__BEGIN_DECLS`.
+    pub begin: Option<String>,
+    /// Macro closing the block, e.g. `__END_DECLS`.
+    pub end: Option<String>,
+}
+
+impl Config {
+    /// Load the configuration from `explicit`, or from the nearest `guardgen.toml`.
+    ///
+    /// @post Without an explicit path and without a `guardgen.toml`, returns the default.
+    pub fn load(explicit: Option<&str>) -> Result<Self, String> {
+        let path = match explicit {
+            Some(path) => PathBuf::from(path),
+            None => match find_config() {
+                Some(path) => path,
+                None => return Ok(Config::default()),
+            },
+        };
+        let text = fs::read_to_string(&path)
+            .map_err(|e| format!("Error reading config '{}': {}", path.display(), e))?;
+        let mut config = Self::parse(&text)
+            .map_err(|e| format!("Error in config '{}': {}", path.display(), e))?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        config.template.path = config.template.path.map(|p| base.join(p));
+        Ok(config)
+    }
+
+    /// Parse the text of a configuration file.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut config: Config = toml::from_str(text).map_err(|e| e.message().to_string())?;
+        config.languages = config
+            .languages
+            .into_iter()
+            .map(|(extension, language)| (extension.trim_start_matches('.').to_string(), language))
+            .collect();
+        Ok(config)
+    }
+}
+
+/// The nearest `guardgen.toml` in the working directory or its ancestors.
+fn find_config() -> Option<PathBuf> {
+    let cwd = std::env::current_dir().ok()?;
+    cwd.ancestors()
+        .map(|dir| dir.join(CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_template_table() {
+        let config = Config::parse(
+            "[template]\npath = \"tools/header.h.in\"\nauthor = \"Jane Doe\"\nproject = \"Acme\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.template.path.as_deref(),
+            Some(Path::new("tools/header.h.in"))
+        );
+        assert_eq!(config.template.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(config.template.project.as_deref(), Some("Acme"));
+        assert!(Config::parse("[template]\nauthr = \"x\"\n").is_err());
+        assert!(Config::parse("").unwrap().template.path.is_none());
+    }
+
+    #[test]
+    fn parses_spdx_table() {
+        let config = Config::parse("[spdx]\nlicense = \"MIT\"\nholder = \"Acme Inc.\"\n").unwrap();
+
+        assert_eq!(config.spdx.license.as_deref(), Some("MIT"));
+        assert_eq!(config.spdx.holder.as_deref(), Some("Acme Inc."));
+        assert_eq!(config.spdx.year, None);
+    }
+
+    #[test]
+    fn parses_languages_table() {
+        let config = Config::parse("[languages]\nh = \"cxx\"\n\".inc\" = \"c\"\n").unwrap();
+
+        assert_eq!(config.languages.get("h"), Some(&crate::Language::Cxx));
+        assert_eq!(config.languages.get("inc"), Some(&crate::Language::C));
+        assert!(Config::parse("[languages]\nh = \"fortran\"\n").is_err());
+    }
+
+    #[test]
+    fn parses_linkage_table() {
+        let config =
+            Config::parse("[linkage]\nbegin = \"__BEGIN_DECLS\"\nend = \"__END_DECLS\"\n").unwrap();
+
+        assert_eq!(config.linkage.begin.as_deref(), Some("__BEGIN_DECLS"));
+        assert_eq!(config.linkage.end.as_deref(), Some("__END_DECLS"));
+    }
+}
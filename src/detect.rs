@@ -0,0 +1,135 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! Inference of the target `Language` of a header from its name and content.
+
+use crate::Language;
+use crate::header::{LineKind, scan};
+use std::path::Path;
+
+/// Default language of each header extension.
+pub const EXTENSIONS: [(&str, Language); 10] = [
+    ("h", Language::C),
+    ("hh", Language::Cxx),
+    ("hpp", Language::Cxx),
+    ("hxx", Language::Cxx),
+    ("inl", Language::Cxx),
+    ("cuh", Language::Cuda),
+    ("clh", Language::OpenCl),
+    ("glsl", Language::Glsl),
+    ("hlsli", Language::Hlsl),
+    ("fxh", Language::Hlsl),
+];
+
+/// Extension used by both C and C++ headers; its default is C.
+pub const AMBIGUOUS_EXTENSION: &str = "h";
+
+/// Keywords that cannot appear in C code.
+const CXX_KEYWORDS: [&str; 12] = [
+    "class",
+    "constexpr",
+    "namespace",
+    "nullptr",
+    "operator",
+    "private",
+    "protected",
+    "public",
+    "template",
+    "typename",
+    "using",
+    "virtual",
+];
+
+/// The default language for the extension of `path`, if it is a known header extension.
+pub fn language_for_path(path: &Path) -> Option<Language> {
+    let extension = path.extension()?.to_str()?;
+    EXTENSIONS
+        .iter()
+        .find(|(known, _)| *known == extension)
+        .map(|&(_, language)| language)
+}
+
+/// Whether a header's content uses constructs only C++ accepts.
+///
+/// Looks for C++-only keywords, `::` and `#include`s of extension-less
+/// standard headers such as `<vector>`. Code in conditionals testing
+/// `__cplusplus` is skipped, since C headers keep their C++ support there.
+pub fn looks_like_cxx(text: &str) -> bool {
+    let lines = scan(text);
+    let mut skip_until: Option<usize> = None;
+    for line in &lines {
+        if let Some(depth) = skip_until {
+            if line.is_directive("endif") && line.depth == depth + 1 {
+                skip_until = None;
+            }
+            continue;
+        }
+        match &line.kind {
+            LineKind::Directive { name, arg }
+                if matches!(name.as_str(), "if" | "ifdef" | "ifndef")
+                    && arg.contains("__cplusplus") =>
+            {
+                skip_until = Some(line.depth);
+            }
+            LineKind::Directive { name, arg } if name == "include" && includes_cxx_header(arg) => {
+                return true;
+            }
+            LineKind::Code(code)
+                if code.contains("::")
+                    || code
+                        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                        .any(|word| CXX_KEYWORDS.contains(&word)) =>
+            {
+                return true;
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Whether an `#include` argument names an extension-less system header like `<vector>`.
+fn includes_cxx_header(arg: &str) -> bool {
+    arg.strip_prefix('<')
+        .and_then(|rest| rest.split('>').next())
+        .is_some_and(|header| !header.contains('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn infers_language_from_extension() {
+        assert_eq!(
+            language_for_path(Path::new("include/a.h")),
+            Some(Language::C)
+        );
+        assert_eq!(language_for_path(Path::new("a.hpp")), Some(Language::Cxx));
+        assert_eq!(language_for_path(Path::new("a.cuh")), Some(Language::Cuda));
+        assert_eq!(
+            language_for_path(Path::new("a.hlsli")),
+            Some(Language::Hlsl)
+        );
+        assert_eq!(language_for_path(Path::new("a.txt")), None);
+        assert_eq!(language_for_path(Path::new("Makefile")), None);
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn detects_cxx_only_constructs() {
+        let c = "#include <stdio.h>\n#ifdef __cplusplus\nextern \"C\" {\n#endif\n\
+                 /* a class of its own */\nint f(void);\n#ifdef __cplusplus\n}\n#endif\n";
+        assert!(!looks_like_cxx(c));
+        assert!(looks_like_cxx("#include <vector>\n"));
+        assert!(looks_like_cxx("namespace acme {\n}\n"));
+        assert!(looks_like_cxx("std::size_t n();\n"));
+        assert!(!looks_like_cxx(
+            "#if defined(__cplusplus)\ntemplate <class T> T f();\n#endif\n"
+        ));
+    }
+}
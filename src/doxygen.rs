@@ -0,0 +1,110 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! Doxygen file blocks, and keeping guard macros out of generated documentation.
+
+use std::fmt;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen::prelude::*;
+
+/// Macro Doxygen configurations conventionally predefine to skip internals.
+pub const SKIP_MACRO: &str = "DOXYGEN_SHOULD_SKIP_THIS";
+
+/// How the guard definition is hidden from Doxygen.
+/// - `None`: The guard is rendered as is.
+/// - `Cond`: `#ifndef`/`#define` sit between `/** @cond */` and `/** @endcond */`.
+/// - `SkipThis`: The `#define` sits inside `#ifndef DOXYGEN_SHOULD_SKIP_THIS`.
+#[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GuardHiding {
+    #[default]
+    None,
+    Cond,
+    SkipThis,
+}
+
+/// Write the `#ifndef`/`#define` lines for `name`, hidden as `hiding` selects.
+pub(crate) fn write_definition<W: fmt::Write + ?Sized>(
+    out: &mut W,
+    name: &dyn fmt::Display,
+    hiding: GuardHiding,
+    newline: &str,
+) -> fmt::Result {
+    match hiding {
+        GuardHiding::None => {
+            write!(out, "#ifndef {}{}", name, newline)?;
+            write!(out, "#define {}{}", name, newline)
+        }
+        GuardHiding::Cond => {
+            write!(out, "/** @cond */{}", newline)?;
+            write!(out, "#ifndef {}{}", name, newline)?;
+            write!(out, "#define {}{}", name, newline)?;
+            write!(out, "/** @endcond */{}", newline)
+        }
+        GuardHiding::SkipThis => {
+            write!(out, "#ifndef {}{}", name, newline)?;
+            write!(out, "#ifndef {}{}", SKIP_MACRO, newline)?;
+            write!(out, "#define {}{}", name, newline)?;
+            write!(out, "#endif /* {} */{}", SKIP_MACRO, newline)
+        }
+    }
+}
+
+/// A `/** @file ... @brief ... */` block for `file_name`, each line ending with `newline`.
+///
+/// An empty `file_name` gives a bare `@file`, which documents the file it appears in.
+pub fn file_block(file_name: &str, brief: Option<&str>, newline: &str) -> String {
+    let mut block = format!("/**{}", newline);
+    if file_name.is_empty() {
+        block.push_str(&format!(" * @file{}", newline));
+    } else {
+        block.push_str(&format!(" * @file {}{}", file_name, newline));
+    }
+    if let Some(brief) = brief {
+        block.push_str(&format!(" * @brief {}{}", brief, newline));
+    }
+    block.push_str(" */");
+    block.push_str(newline);
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{IncludeGuard, Language, LineEnding};
+
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn renders_file_block() {
+        assert_eq!(
+            file_block("socket.h", Some("Socket API."), "\n"),
+            "/**\n * @file socket.h\n * @brief Socket API.\n */\n"
+        );
+        assert_eq!(
+            file_block("a.h", None, "\r\n"),
+            "/**\r\n * @file a.h\r\n */\r\n"
+        );
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn hidden_guards_are_still_recognised() {
+        for hiding in [GuardHiding::Cond, GuardHiding::SkipThis] {
+            let mut guard = IncludeGuard::new("G".to_string(), Language::C, LineEnding::LF);
+            guard.set_hiding(hiding);
+            let text = guard.wrap("int f(void);");
+
+            assert!(text.contains("#define G\n"));
+            assert_eq!(
+                crate::header::check_header(&text, &Default::default()),
+                vec![crate::header::HeaderIssue::NotUuidGuard {
+                    guard: "G".to_string()
+                }]
+            );
+        }
+    }
+}
@@ -0,0 +1,210 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! Preamble placed after the guard's `#define` that stops a header from being
+//! compiled as the wrong language or with a too old standard.
+
+use crate::Language;
+use std::fmt;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen::prelude::*;
+
+/// Minimum language standard a header requires.
+/// - `C99` ... `C23`: Checked against `__STDC_VERSION__` when compiled as C.
+/// - `Cxx11` ... `Cxx23`: Checked against `__cplusplus` (`_MSVC_LANG` on MSVC)
+///   when compiled as C++.
+#[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Standard {
+    C99,
+    C11,
+    C17,
+    C23,
+    Cxx11,
+    Cxx14,
+    Cxx17,
+    Cxx20,
+    Cxx23,
+}
+
+impl Standard {
+    /// Whether this is a C++ standard.
+    pub fn is_cxx(self) -> bool {
+        matches!(
+            self,
+            Standard::Cxx11 | Standard::Cxx14 | Standard::Cxx17 | Standard::Cxx20 | Standard::Cxx23
+        )
+    }
+
+    /// Value of `__STDC_VERSION__` or `__cplusplus` for this standard.
+    pub fn version(self) -> &'static str {
+        match self {
+            Standard::C99 => "199901L",
+            Standard::C11 => "201112L",
+            Standard::C17 => "201710L",
+            Standard::C23 => "202311L",
+            Standard::Cxx11 => "201103L",
+            Standard::Cxx14 => "201402L",
+            Standard::Cxx17 => "201703L",
+            Standard::Cxx20 => "202002L",
+            Standard::Cxx23 => "202302L",
+        }
+    }
+}
+
+impl fmt::Display for Standard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Standard::C99 => "C99",
+            Standard::C11 => "C11",
+            Standard::C17 => "C17",
+            Standard::C23 => "C23",
+            Standard::Cxx11 => "C++11",
+            Standard::Cxx14 => "C++14",
+            Standard::Cxx17 => "C++17",
+            Standard::Cxx20 => "C++20",
+            Standard::Cxx23 => "C++23",
+        })
+    }
+}
+
+/// What the preamble checks; the default checks nothing.
+/// - `language`: The header is compiled by a compiler of its own language:
+///   CUDA (`__CUDACC__`) for CUDA, C++ for the other C++ dialects and C otherwise.
+/// - `standard`: The compiler implements at least this standard. A C standard
+///   only constrains C compilations and a C++ standard only C++ ones.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Enforcement {
+    pub language: bool,
+    pub standard: Option<Standard>,
+}
+
+impl Enforcement {
+    /// Whether the preamble would be empty.
+    pub fn is_empty(&self) -> bool {
+        !self.language && self.standard.is_none()
+    }
+}
+
+/// Whether the language check is meaningful for `language`, i.e. it is a C or C++ dialect.
+pub fn can_enforce_language(language: Language) -> bool {
+    language_check(language).is_some()
+}
+
+/// The conditional rejecting the wrong compiler for `language`, and the compiler it requires.
+fn language_check(language: Language) -> Option<(&'static str, &'static str)> {
+    match language {
+        Language::Cuda => Some(("#ifndef __CUDACC__", "CUDA")),
+        language if language.is_cxx() => Some(("#ifndef __cplusplus", "C++")),
+        Language::C | Language::ObjC | Language::OpenCl => Some(("#ifdef __cplusplus", "C")),
+        _ => None,
+    }
+}
+
+/// Write the preamble for `language`, starting with a blank line; nothing if
+/// `enforcement` checks nothing.
+pub(crate) fn write_preamble<W: fmt::Write + ?Sized>(
+    out: &mut W,
+    enforcement: &Enforcement,
+    language: Language,
+    newline: &str,
+) -> fmt::Result {
+    let check = language_check(language).filter(|_| enforcement.language);
+    if check.is_none() && enforcement.standard.is_none() {
+        return Ok(());
+    }
+    out.write_str(newline)?;
+    if let Some((condition, compiler)) = check {
+        write!(out, "{}{}", condition, newline)?;
+        write!(
+            out,
+            "#error \"This header requires a {} compiler.\"{}",
+            compiler, newline
+        )?;
+        write!(out, "#endif{}", newline)?;
+    }
+    if let Some(standard) = enforcement.standard {
+        if standard.is_cxx() {
+            write!(
+                out,
+                "#if defined(__cplusplus) && (defined(_MSVC_LANG) ? _MSVC_LANG : __cplusplus) < {}{}",
+                standard.version(),
+                newline
+            )?;
+        } else {
+            write!(
+                out,
+                "#if !defined(__cplusplus) && (!defined(__STDC_VERSION__) || __STDC_VERSION__ < {}){}",
+                standard.version(),
+                newline
+            )?;
+        }
+        write!(
+            out,
+            "#error \"This header requires {} or later.\"{}",
+            standard, newline
+        )?;
+        write!(out, "#endif{}", newline)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{IncludeGuard, LineEnding};
+
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn preamble_follows_the_define() {
+        let mut guard = IncludeGuard::new("G".to_string(), Language::Cxx, LineEnding::LF);
+        guard.set_enforcement(Enforcement {
+            language: true,
+            standard: Some(Standard::Cxx17),
+        });
+        assert_eq!(
+            guard.render(),
+            "#ifndef G\n\
+             #define G\n\
+             \n\
+             #ifndef __cplusplus\n\
+             #error \"This header requires a C++ compiler.\"\n\
+             #endif\n\
+             #if defined(__cplusplus) && (defined(_MSVC_LANG) ? _MSVC_LANG : __cplusplus) < 201703L\n\
+             #error \"This header requires C++17 or later.\"\n\
+             #endif\n\
+             \n\
+             #endif /* G */\n"
+        );
+
+        let mut guard = IncludeGuard::new("G".to_string(), Language::C, LineEnding::LF);
+        guard.set_enforcement(Enforcement {
+            language: false,
+            standard: Some(Standard::C11),
+        });
+        assert!(guard.wrap("int f(void);").starts_with(
+            "#ifndef G\n\
+             #define G\n\
+             \n\
+             #if !defined(__cplusplus) && (!defined(__STDC_VERSION__) || __STDC_VERSION__ < 201112L)\n\
+             #error \"This header requires C11 or later.\"\n\
+             #endif\n\
+             \n\
+             #ifdef __cplusplus\n"
+        ));
+        assert!(!can_enforce_language(Language::Glsl));
+
+        let mut guard = IncludeGuard::new("G".to_string(), Language::Cuda, LineEnding::LF);
+        guard.set_enforcement(Enforcement {
+            language: true,
+            standard: None,
+        });
+        assert!(guard.render().contains(
+            "#ifndef __CUDACC__\n#error \"This header requires a CUDA compiler.\"\n#endif\n"
+        ));
+    }
+}
@@ -0,0 +1,278 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! Generated include guards as values that can be placed around existing content.
+
+use crate::alias;
+use crate::doxygen::{self, GuardHiding};
+use crate::enforce::{self, Enforcement};
+use crate::linkage::Linkage;
+use crate::render::{self, BodyBlock, Closing, write_closing};
+use crate::{Language, LineEnding};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen::prelude::*;
+
+/// An include guard: its macro name plus the language and line ending it is rendered with.
+///
+/// @invariant `render()` equals `opening()` and `closing()` around an empty body,
+///            i.e. the text `IncludeGuardGenerator::generate` returns.
+#[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IncludeGuard {
+    name: String,
+    language: Language,
+    line_ending: LineEnding,
+    hiding: GuardHiding,
+    linkage: Linkage,
+    assembler_safe: bool,
+    enforcement: Enforcement,
+    alias: Option<String>,
+    strict: bool,
+}
+
+#[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen)]
+impl IncludeGuard {
+    /// Create a guard with an explicit macro name.
+    ///
+    /// @pre `name` is a valid preprocessor identifier.
+    #[cfg_attr(
+        all(target_arch = "wasm32", target_os = "unknown"),
+        wasm_bindgen(constructor)
+    )]
+    pub fn new(name: String, language: Language, line_ending: LineEnding) -> Self {
+        IncludeGuard {
+            name,
+            language,
+            line_ending,
+            hiding: GuardHiding::None,
+            linkage: Linkage::ExternC,
+            assembler_safe: false,
+            enforcement: Enforcement::default(),
+            alias: None,
+            strict: false,
+        }
+    }
+
+    /// Hide the guard definition from Doxygen as `hiding` selects.
+    #[cfg_attr(
+        all(target_arch = "wasm32", target_os = "unknown"),
+        wasm_bindgen(js_name = setHiding)
+    )]
+    pub fn set_hiding(&mut self, hiding: GuardHiding) {
+        self.hiding = hiding;
+    }
+
+    /// Keep the guard visible to the assembler but place everything else
+    /// (body block and body) inside `#ifndef __ASSEMBLER__`.
+    #[cfg_attr(
+        all(target_arch = "wasm32", target_os = "unknown"),
+        wasm_bindgen(js_name = setAssemblerSafe)
+    )]
+    pub fn set_assembler_safe(&mut self, assembler_safe: bool) {
+        self.assembler_safe = assembler_safe;
+    }
+
+    /// The guard macro name.
+    #[cfg_attr(
+        all(target_arch = "wasm32", target_os = "unknown"),
+        wasm_bindgen(getter)
+    )]
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// The `#ifndef`/`#define` lines, followed by the assembler check (if enabled),
+    /// the enforcement preamble and the opening body block of the language.
+    pub fn opening(&self) -> String {
+        let mut text = String::new();
+        doxygen::write_definition(&mut text, &self.name, self.hiding, self.newline())
+            .expect("writing to a String cannot fail");
+        self.push_alias(&mut text);
+        if self.assembler_safe {
+            render::write_assembler_open(&mut text, self.newline())
+                .expect("writing to a String cannot fail");
+        }
+        enforce::write_preamble(&mut text, &self.enforcement, self.language, self.newline())
+            .expect("writing to a String cannot fail");
+        if let Some(block) = BodyBlock::of(self.language, &self.linkage) {
+            block
+                .write_open(&mut text, self.newline())
+                .expect("writing to a String cannot fail");
+        }
+        text
+    }
+
+    /// The closing body block of the language, the end of the assembler check
+    /// and the strict `#else` branch (if enabled), followed by the `#endif` line.
+    pub fn closing(&self) -> String {
+        let mut text = String::new();
+        write_closing(
+            &mut text,
+            &self.name,
+            self.language,
+            &self.linkage,
+            Closing {
+                assembler_safe: self.assembler_safe,
+                strict: self.strict,
+            },
+            self.newline(),
+        )
+        .expect("writing to a String cannot fail");
+        text
+    }
+
+    /// The complete guard with nothing between its opening and closing parts.
+    pub fn render(&self) -> String {
+        self.wrap("")
+    }
+
+    /// Place `body` between the opening and closing parts, separated by blank lines.
+    ///
+    /// @post Line endings inside `body` are converted to the guard's line ending,
+    ///       and leading/trailing blank lines of `body` are dropped.
+    pub fn wrap(&self, body: &str) -> String {
+        self.wrap_split("", body)
+    }
+}
+
+impl IncludeGuard {
+    /// Use `linkage` for the C linkage block of `Language::C`.
+    pub fn set_linkage(&mut self, linkage: Linkage) {
+        self.linkage = linkage;
+    }
+
+    /// The C linkage block used for `Language::C`.
+    pub fn linkage(&self) -> &Linkage {
+        &self.linkage
+    }
+
+    /// Emit `enforcement` after the `#define` (inside the assembler check, if any).
+    pub fn set_enforcement(&mut self, enforcement: Enforcement) {
+        self.enforcement = enforcement;
+    }
+
+    /// What the preamble after the `#define` checks.
+    pub fn enforcement(&self) -> &Enforcement {
+        &self.enforcement
+    }
+
+    /// Also define `alias` right after the guard macro, as a name other code can test.
+    ///
+    /// @pre `alias` is a valid preprocessor identifier.
+    pub fn set_alias(&mut self, alias: Option<String>) {
+        self.alias = alias;
+    }
+
+    /// The alias macro defined next to the guard macro, if any.
+    pub fn alias(&self) -> Option<&str> {
+        self.alias.as_deref()
+    }
+
+    /// Append the `#define` of the alias, if any, marked unless its suffix identifies it.
+    fn push_alias(&self, text: &mut String) {
+        if let Some(alias) = &self.alias {
+            text.push_str("#define ");
+            text.push_str(alias);
+            if alias::needs_marker(alias) {
+                let (open, close) = render::comment_delimiters(self.language);
+                text.push(' ');
+                text.push_str(open);
+                text.push_str(alias::ALIAS_MARKER);
+                text.push_str(close);
+            }
+            text.push_str(self.newline());
+        }
+    }
+
+    /// Make a second inclusion an error: the guard gets an `#else` branch with `#error`.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Whether a second inclusion is an error.
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Whether the content inside the guard is hidden from the assembler.
+    pub fn assembler_safe(&self) -> bool {
+        self.assembler_safe
+    }
+
+    /// The guard macro name, borrowed.
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+
+    /// The language the guard is rendered for.
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// How the guard definition is hidden from Doxygen.
+    pub fn hiding(&self) -> GuardHiding {
+        self.hiding
+    }
+
+    /// The line ending the guard is rendered with.
+    pub fn line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    /// The newline sequence the guard is rendered with.
+    pub fn newline(&self) -> &'static str {
+        render::newline(self.line_ending)
+    }
+
+    /// Like `wrap`, but `head` goes between the guard lines and the linkage block.
+    ///
+    /// `head` holds content that must not get C linkage, such as `#include` lines.
+    pub(crate) fn wrap_split(&self, head: &str, body: &str) -> String {
+        let newline = self.newline();
+        let push_lines = |text: &mut String, lines: &str| {
+            for line in lines.lines() {
+                text.push_str(line);
+                text.push_str(newline);
+            }
+        };
+        let trim =
+            |text: &'_ str| -> String { text.trim_matches(|c| c == '\n' || c == '\r').to_string() };
+        let (head, body) = (trim(head), trim(body));
+
+        let mut text = String::new();
+        doxygen::write_definition(&mut text, &self.name, self.hiding, newline)
+            .expect("writing to a String cannot fail");
+        self.push_alias(&mut text);
+        if self.assembler_safe {
+            render::write_assembler_open(&mut text, newline)
+                .expect("writing to a String cannot fail");
+        }
+        let before_preamble = text.len();
+        enforce::write_preamble(&mut text, &self.enforcement, self.language, newline)
+            .expect("writing to a String cannot fail");
+        let preamble = text.len() > before_preamble;
+        if !head.is_empty() {
+            text.push_str(newline);
+            push_lines(&mut text, &head);
+        }
+        let block = BodyBlock::of(self.language, &self.linkage);
+        if let Some(block) = &block {
+            block
+                .write_open(&mut text, newline)
+                .expect("writing to a String cannot fail");
+        }
+        if body.is_empty() {
+            // Without a body only a block needs a blank line between its two halves.
+            if block.is_some() || self.assembler_safe || preamble {
+                text.push_str(newline);
+            }
+        } else {
+            text.push_str(newline);
+            push_lines(&mut text, &body);
+            text.push_str(newline);
+        }
+        text.push_str(&self.closing());
+        text
+    }
+}
@@ -0,0 +1,1344 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! Analysis and rewriting of existing header files.
+//!
+//! The scanner is line based: it strips comments, recognises preprocessor
+//! directives and tracks conditional nesting. That is enough to locate an
+//! include guard, report problems with it, and place a new guard or linkage
+//! block without disturbing the rest of the file.
+
+use crate::linkage::Linkage;
+use crate::namespace::is_identifier;
+use crate::render::BodyBlock;
+use crate::{IncludeGuard, Language, LineEnding, UuidKind, alias, doxygen};
+use std::fmt;
+
+/// Text of a comment marking a header as intentionally unguarded, such as an
+/// X-macro `.def` file included many times: `/* guardgen: unguarded */`.
+///
+/// Only the comments before the first directive or code line count.
+pub const UNGUARDED_MARKER: &str = "guardgen: unguarded";
+
+/// Line-ending style observed in a file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LineEndings {
+    /// The file has no line breaks at all.
+    None,
+    LF,
+    CRLF,
+    /// Both LF and CRLF line breaks occur.
+    Mixed,
+}
+
+/// Include guard located in an existing header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExistingGuard {
+    /// The macro tested by the guard's `#ifndef` (or `#if !defined`).
+    pub name: String,
+    /// Zero-based index of the `#ifndef` line.
+    pub ifndef_line: usize,
+    /// Index of the `#define` of the same macro directly after `#ifndef`, if present.
+    pub define_line: Option<usize>,
+    /// Index of the `#endif` closing the guard's conditional, if present.
+    pub endif_line: Option<usize>,
+    /// Index of the guard's `#else`, present in strict guards that reject a second inclusion.
+    pub else_line: Option<usize>,
+    /// Alias macro: a `#define` without a value directly after the guard's `#define`.
+    pub alias: Option<String>,
+}
+
+/// Components of a guard macro name following the `<prefix>_<UUID>[_<suffix>]` scheme.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GuardNameParts {
+    /// Everything before the UUID (without the separating underscore).
+    pub prefix: String,
+    /// The UUID in its canonical hyphenated, uppercase form.
+    pub uuid: String,
+    /// The UUID version, if it is one of the versions guardgen generates.
+    pub uuid_kind: Option<UuidKind>,
+    /// The Unix timestamp in milliseconds carried by a v7 UUID.
+    pub unix_millis: Option<u64>,
+    /// Everything after the UUID (without the separating underscore), if anything.
+    pub suffix: Option<String>,
+}
+
+impl GuardNameParts {
+    /// Split `name` around the first embedded UUID.
+    ///
+    /// @post Returns `None` when `name` contains no `8_4_4_4_12` hexadecimal group sequence.
+    pub fn parse(name: &str) -> Option<Self> {
+        const GROUPS: [usize; 5] = [8, 4, 4, 4, 12];
+        const LENGTH: usize = 36;
+
+        let bytes = name.as_bytes();
+        let matches_at = |start: usize| {
+            let mut pos = start;
+            for (i, len) in GROUPS.iter().enumerate() {
+                if i > 0 {
+                    if bytes[pos] != b'_' {
+                        return false;
+                    }
+                    pos += 1;
+                }
+                if !bytes[pos..pos + len].iter().all(u8::is_ascii_hexdigit) {
+                    return false;
+                }
+                pos += len;
+            }
+            // Refuse matches that are part of a longer hexadecimal run.
+            let bounded_before = start == 0 || !bytes[start - 1].is_ascii_hexdigit();
+            let bounded_after = pos == bytes.len() || !bytes[pos].is_ascii_hexdigit();
+            bounded_before && bounded_after
+        };
+
+        let start = (0..=bytes.len().checked_sub(LENGTH)?).find(|&start| matches_at(start))?;
+        let end = start + LENGTH;
+        let uuid = uuid::Uuid::parse_str(&name[start..end].replace('_', "-")).ok()?;
+
+        let uuid_kind = match uuid.get_version_num() {
+            4 => Some(UuidKind::V4),
+            7 => Some(UuidKind::V7),
+            _ => None,
+        };
+        let unix_millis = match uuid_kind {
+            Some(UuidKind::V7) => uuid.get_timestamp().map(|ts| {
+                let (seconds, nanos) = ts.to_unix();
+                seconds * 1000 + u64::from(nanos / 1_000_000)
+            }),
+            _ => None,
+        };
+        let suffix = name[end..].trim_start_matches('_');
+
+        Some(GuardNameParts {
+            prefix: name[..start].trim_end_matches('_').to_string(),
+            uuid: uuid
+                .hyphenated()
+                .encode_upper(&mut uuid::Uuid::encode_buffer())
+                .to_string(),
+            uuid_kind,
+            unix_millis,
+            suffix: (!suffix.is_empty()).then(|| suffix.to_string()),
+        })
+    }
+}
+
+/// Classification of one source line after comments have been stripped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum LineKind {
+    /// Empty or whitespace-only line.
+    Blank,
+    /// Line containing only comments.
+    Comment,
+    /// Preprocessor directive, e.g. `name = "ifndef"`, `arg = "FOO_H"`.
+    Directive { name: String, arg: String },
+    /// Anything else.
+    Code(String),
+}
+
+/// Per-line view of a file: original text plus its classification.
+#[derive(Clone, Debug)]
+pub(crate) struct ScannedLine<'a> {
+    /// The line including its terminator, exactly as in the file.
+    pub(crate) raw: &'a str,
+    pub(crate) kind: LineKind,
+    /// Conditional nesting depth before this line.
+    pub(crate) depth: usize,
+}
+
+impl ScannedLine<'_> {
+    /// Whether this line is a directive with the given name.
+    pub(crate) fn is_directive(&self, directive: &str) -> bool {
+        matches!(&self.kind, LineKind::Directive { name, .. } if name == directive)
+    }
+
+    /// Whether the line contributes anything besides whitespace and comments.
+    pub(crate) fn is_significant(&self) -> bool {
+        matches!(self.kind, LineKind::Directive { .. } | LineKind::Code(_))
+    }
+}
+
+/// Split `text` into classified lines.
+pub(crate) fn scan(text: &str) -> Vec<ScannedLine<'_>> {
+    let mut in_block_comment = false;
+    let mut depth = 0usize;
+    let mut lines = Vec::new();
+
+    for raw in text.split_inclusive('\n') {
+        let (stripped, had_comment) = strip_comments(raw, &mut in_block_comment);
+        let trimmed = stripped.trim();
+
+        let kind = if trimmed.is_empty() {
+            if had_comment {
+                LineKind::Comment
+            } else {
+                LineKind::Blank
+            }
+        } else if let Some(rest) = trimmed.strip_prefix('#') {
+            let rest = rest.trim_start();
+            let name_len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            LineKind::Directive {
+                name: rest[..name_len].to_string(),
+                arg: rest[name_len..].trim().to_string(),
+            }
+        } else {
+            LineKind::Code(trimmed.to_string())
+        };
+
+        let line_depth = depth;
+        if let LineKind::Directive { name, .. } = &kind {
+            match name.as_str() {
+                "if" | "ifdef" | "ifndef" => depth += 1,
+                "endif" => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        lines.push(ScannedLine {
+            raw,
+            kind,
+            depth: line_depth,
+        });
+    }
+    lines
+}
+
+/// Remove comments from one line, carrying block-comment state across lines.
+///
+/// String and character literals are kept intact so `extern "C"` survives.
+fn strip_comments(line: &str, in_block_comment: &mut bool) -> (String, bool) {
+    let mut out = String::with_capacity(line.len());
+    let mut had_comment = *in_block_comment;
+    let mut chars = line.chars().peekable();
+    let mut quote: Option<char> = None;
+
+    while let Some(c) = chars.next() {
+        if *in_block_comment {
+            if c == '*' && chars.peek() == Some(&'/') {
+                chars.next();
+                *in_block_comment = false;
+                out.push(' ');
+            }
+            continue;
+        }
+        if let Some(q) = quote {
+            out.push(c);
+            if c == '\\' {
+                if let Some(escaped) = chars.next() {
+                    out.push(escaped);
+                }
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => {
+                quote = Some(c);
+                out.push(c);
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                *in_block_comment = true;
+                had_comment = true;
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                had_comment = true;
+                break;
+            }
+            _ => out.push(c),
+        }
+    }
+    (out, had_comment)
+}
+
+/// First whitespace-separated token of a directive argument.
+fn first_token(arg: &str) -> &str {
+    arg.split_whitespace().next().unwrap_or("")
+}
+
+/// Macro tested by `#ifndef X`, `#if !defined(X)` or `#if !defined X`.
+fn guard_macro(line: &ScannedLine<'_>) -> Option<String> {
+    let LineKind::Directive { name, arg } = &line.kind else {
+        return None;
+    };
+    match name.as_str() {
+        "ifndef" => Some(first_token(arg).to_string()).filter(|m| !m.is_empty()),
+        "if" => {
+            let rest = arg
+                .strip_prefix('!')?
+                .trim_start()
+                .strip_prefix("defined")?;
+            let rest = rest.trim();
+            let inner = match rest.strip_prefix('(') {
+                Some(inner) => inner.strip_suffix(')')?.trim(),
+                None => rest,
+            };
+            let valid =
+                !inner.is_empty() && inner.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            valid.then(|| inner.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Whether the line is `#pragma once`.
+fn is_pragma_once(line: &ScannedLine<'_>) -> bool {
+    matches!(&line.kind, LineKind::Directive { name, arg } if name == "pragma" && first_token(arg) == "once")
+}
+
+/// Structural facts about an existing header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderInfo {
+    /// The include guard, if the first significant line opens one.
+    pub guard: Option<ExistingGuard>,
+    /// Whether the file contains `#pragma once`.
+    pub pragma_once: bool,
+    /// Whether a comment line before the first significant line holds `UNGUARDED_MARKER`.
+    pub unguarded: bool,
+    /// Whether the file contains an `extern "C" {` linkage block.
+    pub has_linkage_block: bool,
+    /// Line-ending style of the file.
+    pub line_endings: LineEndings,
+    /// First line with code after the guard's `#endif`, if any.
+    pub code_after_guard: Option<usize>,
+}
+
+impl HeaderInfo {
+    /// Analyse the text of a header.
+    pub fn analyze(text: &str) -> Self {
+        let lines = scan(text);
+
+        // `#pragma once` may precede the guard; anything else significant may not.
+        let first = lines
+            .iter()
+            .position(|line| line.is_significant() && !is_pragma_once(line));
+        let guard = first.and_then(|ifndef_line| {
+            let name = guard_macro(&lines[ifndef_line])?;
+            let next_significant = |after: usize| {
+                lines[after + 1..]
+                    .iter()
+                    .position(ScannedLine::is_significant)
+                    .map(|offset| after + 1 + offset)
+            };
+            let is_define = |i: usize| {
+                matches!(&lines[i].kind, LineKind::Directive { name: d, arg } if d == "define" && first_token(arg) == name)
+            };
+            // The `#define` may be hidden from Doxygen inside `#ifndef DOXYGEN_SHOULD_SKIP_THIS`.
+            let define_line = next_significant(ifndef_line).and_then(|i| {
+                if is_define(i) {
+                    Some(i)
+                } else if guard_macro(&lines[i]).as_deref() == Some(doxygen::SKIP_MACRO) {
+                    next_significant(i).filter(|&j| is_define(j))
+                } else {
+                    None
+                }
+            });
+            let alias = define_line.and_then(|define| {
+                let mut next = next_significant(define)?;
+                // Past the `#endif` of `DOXYGEN_SHOULD_SKIP_THIS`.
+                if lines[next].is_directive("endif")
+                    && lines[next].depth == lines[ifndef_line].depth + 2
+                {
+                    next = next_significant(next)?;
+                }
+                // Only a marked define or one named like the default template is an
+                // alias; feature macros such as `_GNU_SOURCE` are not.
+                match &lines[next].kind {
+                    LineKind::Directive { name: d, arg }
+                        if d == "define"
+                            && arg != &name
+                            && is_identifier(arg)
+                            && (!alias::needs_marker(arg)
+                                || lines[next].raw.contains(alias::ALIAS_MARKER)) =>
+                    {
+                        Some(arg.clone())
+                    }
+                    _ => None,
+                }
+            });
+            let endif_line = lines[ifndef_line + 1..]
+                .iter()
+                .position(|line| line.is_directive("endif") && line.depth == lines[ifndef_line].depth + 1)
+                .map(|offset| ifndef_line + 1 + offset);
+            let else_line = lines[ifndef_line + 1..endif_line.unwrap_or(lines.len())]
+                .iter()
+                .position(|line| line.is_directive("else") && line.depth == lines[ifndef_line].depth + 1)
+                .map(|offset| ifndef_line + 1 + offset);
+            Some(ExistingGuard {
+                name,
+                ifndef_line,
+                define_line,
+                endif_line,
+                else_line,
+                alias,
+            })
+        });
+
+        let code_after_guard = guard.as_ref().and_then(|g| {
+            let endif = g.endif_line?;
+            lines[endif + 1..]
+                .iter()
+                .position(ScannedLine::is_significant)
+                .map(|offset| endif + 1 + offset)
+        });
+
+        HeaderInfo {
+            guard,
+            pragma_once: lines.iter().any(is_pragma_once),
+            unguarded: lines
+                .iter()
+                .take_while(|line| !line.is_significant())
+                .any(|line| line.kind == LineKind::Comment && line.raw.contains(UNGUARDED_MARKER)),
+            has_linkage_block: lines
+                .iter()
+                .any(|line| opens_linkage_block(line, &Linkage::ExternC)),
+            line_endings: detect_line_endings(text),
+            code_after_guard,
+        }
+    }
+}
+
+/// Whether the line opens the linkage block `linkage` generates.
+fn opens_linkage_block(line: &ScannedLine<'_>, linkage: &Linkage) -> bool {
+    match &line.kind {
+        LineKind::Code(code) => linkage.opens(code),
+        _ => false,
+    }
+}
+
+/// Determine the line-ending style of `text`.
+pub fn detect_line_endings(text: &str) -> LineEndings {
+    let crlf = text.matches("\r\n").count();
+    let lf = text.matches('\n').count() - crlf;
+    match (lf, crlf) {
+        (0, 0) => LineEndings::None,
+        (_, 0) => LineEndings::LF,
+        (0, _) => LineEndings::CRLF,
+        _ => LineEndings::Mixed,
+    }
+}
+
+/// Expectations a header is checked against. `None` fields are not checked.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CheckOptions {
+    pub prefix: Option<String>,
+    pub suffix: Option<String>,
+    pub uuid_kind: Option<UuidKind>,
+    pub language: Option<Language>,
+    pub line_ending: Option<LineEnding>,
+    /// The linkage block expected for `Language::C`.
+    pub linkage: Linkage,
+    /// Audit the linkage blocks: functions declared outside them, unbalanced
+    /// blocks and `#include`s inside them.
+    pub audit_linkage: bool,
+}
+
+/// Problem found in a header by `check_header`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HeaderIssue {
+    /// The first significant line does not open an include guard.
+    MissingGuard,
+    /// The `#ifndef` is not directly followed by a `#define` of the same macro.
+    MismatchedDefine {
+        guard: String,
+    },
+    /// The guard's conditional is never closed.
+    UnterminatedGuard {
+        guard: String,
+    },
+    /// Code follows the guard's `#endif` (1-based line number).
+    CodeAfterGuard {
+        line: usize,
+    },
+    /// The guard macro does not embed a UUID.
+    NotUuidGuard {
+        guard: String,
+    },
+    PrefixMismatch {
+        expected: String,
+        found: String,
+    },
+    SuffixMismatch {
+        expected: String,
+        found: Option<String>,
+    },
+    UuidVersionMismatch {
+        expected: UuidKind,
+        found: Option<UuidKind>,
+    },
+    /// `Language::C` was requested but the header has no `extern "C"` block.
+    MissingLinkageBlock,
+    /// A function is declared outside any linkage block (1-based line number).
+    FunctionWithoutLinkage {
+        line: usize,
+    },
+    /// A linkage block is opened but never closed, or closed without being opened.
+    UnbalancedLinkage {
+        line: usize,
+    },
+    /// An `#include` inside a linkage block gives the included header C linkage.
+    IncludeInLinkage {
+        line: usize,
+    },
+    LineEndingMismatch {
+        expected: LineEnding,
+        found: LineEndings,
+    },
+}
+
+impl fmt::Display for HeaderIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeaderIssue::MissingGuard => write!(f, "missing include guard"),
+            HeaderIssue::MismatchedDefine { guard } => {
+                write!(
+                    f,
+                    "'#ifndef {}' is not followed by '#define {}'",
+                    guard, guard
+                )
+            }
+            HeaderIssue::UnterminatedGuard { guard } => {
+                write!(f, "include guard '{}' has no matching '#endif'", guard)
+            }
+            HeaderIssue::CodeAfterGuard { line } => {
+                write!(f, "line {} is outside the include guard", line)
+            }
+            HeaderIssue::NotUuidGuard { guard } => {
+                write!(f, "include guard '{}' is not UUID-based", guard)
+            }
+            HeaderIssue::PrefixMismatch { expected, found } => {
+                write!(f, "guard prefix is '{}', expected '{}'", found, expected)
+            }
+            HeaderIssue::SuffixMismatch { expected, found } => match found {
+                Some(found) => write!(f, "guard suffix is '{}', expected '{}'", found, expected),
+                None => write!(f, "guard has no suffix, expected '{}'", expected),
+            },
+            HeaderIssue::UuidVersionMismatch { expected, found } => {
+                let name = |kind: &UuidKind| match kind {
+                    UuidKind::V4 => "v4",
+                    UuidKind::V7 => "v7",
+                };
+                match found {
+                    Some(found) => write!(
+                        f,
+                        "guard UUID is {}, expected {}",
+                        name(found),
+                        name(expected)
+                    ),
+                    None => write!(f, "guard UUID version is not {}", name(expected)),
+                }
+            }
+            HeaderIssue::MissingLinkageBlock => write!(f, "missing extern \"C\" linkage block"),
+            HeaderIssue::FunctionWithoutLinkage { line } => write!(
+                f,
+                "function declared outside an extern \"C\" linkage block on line {}",
+                line
+            ),
+            HeaderIssue::UnbalancedLinkage { line } => {
+                write!(f, "unbalanced linkage block on line {}", line)
+            }
+            HeaderIssue::IncludeInLinkage { line } => {
+                write!(f, "#include inside the linkage block on line {}", line)
+            }
+            HeaderIssue::LineEndingMismatch { expected, found } => {
+                write!(f, "line endings are {:?}, expected {:?}", found, expected)
+            }
+        }
+    }
+}
+
+/// Check `text` against the structural rules and `options`.
+///
+/// @post The result is empty exactly when the header passes every check.
+pub fn check_header(text: &str, options: &CheckOptions) -> Vec<HeaderIssue> {
+    let info = HeaderInfo::analyze(text);
+    let mut issues = Vec::new();
+
+    match &info.guard {
+        None if info.unguarded => {}
+        None => issues.push(HeaderIssue::MissingGuard),
+        Some(guard) => {
+            if guard.define_line.is_none() {
+                issues.push(HeaderIssue::MismatchedDefine {
+                    guard: guard.name.clone(),
+                });
+            }
+            if guard.endif_line.is_none() {
+                issues.push(HeaderIssue::UnterminatedGuard {
+                    guard: guard.name.clone(),
+                });
+            }
+            if let Some(line) = info.code_after_guard {
+                issues.push(HeaderIssue::CodeAfterGuard { line: line + 1 });
+            }
+            match GuardNameParts::parse(&guard.name) {
+                None => issues.push(HeaderIssue::NotUuidGuard {
+                    guard: guard.name.clone(),
+                }),
+                Some(parts) => {
+                    if let Some(expected) = &options.prefix
+                        && *expected != parts.prefix
+                    {
+                        issues.push(HeaderIssue::PrefixMismatch {
+                            expected: expected.clone(),
+                            found: parts.prefix.clone(),
+                        });
+                    }
+                    if let Some(expected) = &options.suffix
+                        && Some(expected) != parts.suffix.as_ref()
+                    {
+                        issues.push(HeaderIssue::SuffixMismatch {
+                            expected: expected.clone(),
+                            found: parts.suffix.clone(),
+                        });
+                    }
+                    if let Some(expected) = options.uuid_kind
+                        && Some(expected) != parts.uuid_kind
+                    {
+                        issues.push(HeaderIssue::UuidVersionMismatch {
+                            expected,
+                            found: parts.uuid_kind,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    let has_linkage_block = match options.linkage {
+        Linkage::ExternC => info.has_linkage_block,
+        ref linkage => scan(text)
+            .iter()
+            .any(|line| opens_linkage_block(line, linkage)),
+    };
+    // Unguarded headers such as X-macro files are fragments, not linkage units.
+    let fragment = info.guard.is_none() && info.unguarded;
+    let missing_linkage =
+        options.language.is_some_and(Language::has_c_linkage) && !has_linkage_block && !fragment;
+    if missing_linkage {
+        issues.push(HeaderIssue::MissingLinkageBlock);
+    }
+    if options.audit_linkage {
+        issues.extend(
+            audit_linkage(&scan(text), &options.linkage)
+                .into_iter()
+                .filter(|issue| {
+                    !(missing_linkage
+                        && matches!(issue, HeaderIssue::FunctionWithoutLinkage { .. }))
+                }),
+        );
+    }
+
+    if let Some(expected) = options.line_ending {
+        let found = info.line_endings;
+        let matches = match expected {
+            LineEnding::None => true,
+            LineEnding::LF => matches!(found, LineEndings::LF | LineEndings::None),
+            LineEnding::CRLF => matches!(found, LineEndings::CRLF | LineEndings::None),
+        };
+        if !matches {
+            issues.push(HeaderIssue::LineEndingMismatch { expected, found });
+        }
+    }
+
+    issues
+}
+
+/// Audit the linkage blocks of a header.
+///
+/// Blocks are recognised by the text `Language::C` generates: `extern "C" {`
+/// up to its matching brace, or the `linkage` macro pair. Reports the first
+/// function declared outside any block, blocks that are not balanced, and
+/// `#include`s inside a block.
+fn audit_linkage(lines: &[ScannedLine<'_>], linkage: &Linkage) -> Vec<HeaderIssue> {
+    /// An open block: its line and, for `extern "C" {`, the brace depth outside it.
+    struct Block {
+        line: usize,
+        braces: Option<usize>,
+    }
+    let mut issues = Vec::new();
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut braces = 0usize;
+    let mut statement: Option<(usize, String)> = None;
+    let mut reported_function = false;
+    let mut in_directive = false;
+
+    for (i, line) in lines.iter().enumerate() {
+        // Continuation lines of a multi-line macro are part of its directive.
+        let continued = in_directive;
+        in_directive = (continued || matches!(line.kind, LineKind::Directive { .. }))
+            && line.raw.trim_end().ends_with('\\');
+        if continued {
+            continue;
+        }
+        let code = match &line.kind {
+            LineKind::Directive { name, .. } if name == "include" && !blocks.is_empty() => {
+                issues.push(HeaderIssue::IncludeInLinkage { line: i + 1 });
+                continue;
+            }
+            LineKind::Code(code) => code,
+            _ => continue,
+        };
+        if Linkage::ExternC.opens(code) {
+            blocks.push(Block {
+                line: i,
+                braces: Some(braces),
+            });
+            braces += 1;
+            continue;
+        }
+        if linkage.opens(code) {
+            blocks.push(Block {
+                line: i,
+                braces: None,
+            });
+            continue;
+        }
+        if linkage.closes(code) {
+            match blocks.last() {
+                Some(Block { braces: None, .. }) => {
+                    blocks.pop();
+                }
+                _ => issues.push(HeaderIssue::UnbalancedLinkage { line: i + 1 }),
+            }
+            continue;
+        }
+
+        // Declarations directly inside the file or a block; braces of blocks do not count.
+        let scope = blocks.iter().filter(|b| b.braces.is_some()).count();
+        for c in code.chars() {
+            match c {
+                '{' => {
+                    braces += 1;
+                    statement = None;
+                }
+                '}' => {
+                    statement = None;
+                    if braces == 0 {
+                        issues.push(HeaderIssue::UnbalancedLinkage { line: i + 1 });
+                        continue;
+                    }
+                    braces -= 1;
+                    if blocks.last().is_some_and(|b| b.braces == Some(braces)) {
+                        blocks.pop();
+                    }
+                }
+                ';' if braces == scope => {
+                    if let Some((start, text)) = statement.take()
+                        && blocks.is_empty()
+                        && !reported_function
+                        && declares_function(&text)
+                    {
+                        issues.push(HeaderIssue::FunctionWithoutLinkage { line: start + 1 });
+                        reported_function = true;
+                    }
+                }
+                c if braces == scope => {
+                    statement
+                        .get_or_insert_with(|| (i, String::new()))
+                        .1
+                        .push(c);
+                }
+                _ => {}
+            }
+        }
+        if let Some((_, text)) = &mut statement {
+            text.push(' ');
+        }
+    }
+    issues.extend(blocks.iter().map(|block| HeaderIssue::UnbalancedLinkage {
+        line: block.line + 1,
+    }));
+    issues.sort_by_key(|issue| match issue {
+        HeaderIssue::FunctionWithoutLinkage { line }
+        | HeaderIssue::UnbalancedLinkage { line }
+        | HeaderIssue::IncludeInLinkage { line } => *line,
+        _ => 0,
+    });
+    issues
+}
+
+/// Whether a statement (without its `;`) declares a function with external linkage.
+///
+/// Needs a return type before the name, so macro invocations such as
+/// `DECLARE_HANDLE(x)` are not mistaken for declarations.
+fn declares_function(statement: &str) -> bool {
+    let Some(paren) = statement.find('(') else {
+        return false;
+    };
+    let head = statement[..paren].trim_end();
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let words: Vec<&str> = head
+        .split(|c| !is_ident(c))
+        .filter(|w| !w.is_empty())
+        .collect();
+    head.ends_with(is_ident)
+        && !head.contains('=')
+        && words.len() >= 2
+        && !matches!(
+            words[0],
+            "typedef" | "static" | "_Static_assert" | "static_assert"
+        )
+}
+
+/// Surround the content of an unguarded header with `guard`.
+///
+/// Leading comments (license banners, file docs) stay above the guard. For
+/// languages with a body block (the C linkage block, the Objective-C
+/// nullability region) the block opens after the last `#include` or
+/// `#import`, or after the conditional holding it, so standard headers are
+/// never included inside it.
+///
+/// @post `HeaderInfo::analyze` of the result finds `guard`.
+pub fn add_guard(text: &str, guard: &IncludeGuard) -> String {
+    let lines = scan(text);
+    let banner_end = lines
+        .iter()
+        .position(ScannedLine::is_significant)
+        .unwrap_or(lines.len());
+    // Keep trailing blank lines of the banner out of it.
+    let banner_end = lines[..banner_end]
+        .iter()
+        .rposition(|line| line.kind == LineKind::Comment)
+        .map_or(0, |i| i + 1);
+
+    let content = &lines[banner_end..];
+    let split = match BodyBlock::of(guard.language(), guard.linkage()) {
+        Some(_) => after_last_include(content, 0).unwrap_or(0),
+        None => 0,
+    };
+
+    let join = |lines: &[ScannedLine<'_>]| lines.iter().map(|line| line.raw).collect::<String>();
+    let mut out = join(&lines[..banner_end]);
+    if !out.is_empty() {
+        out.push_str(guard.newline());
+    }
+    out.push_str(&guard.wrap_split(&join(&content[..split]), &join(&content[split..])));
+    out
+}
+
+/// Index just past the last `#include` or `#import` of `lines`, which start at
+/// conditional depth `depth`. An include inside a conditional counts up to the
+/// `#endif` of the outermost conditional around it.
+fn after_last_include(lines: &[ScannedLine<'_>], depth: usize) -> Option<usize> {
+    let last = lines.iter().rposition(|line| {
+        (line.is_directive("include") || line.is_directive("import")) && line.depth >= depth
+    })?;
+    if lines[last].depth == depth {
+        return Some(last + 1);
+    }
+    let endif = lines[last + 1..]
+        .iter()
+        .position(|line| line.is_directive("endif") && line.depth == depth + 1);
+    Some(endif.map_or(lines.len(), |offset| last + 1 + offset + 1))
+}
+
+/// Add the `Language::C` linkage block to a guarded header that has none.
+///
+/// The block opens after the last `#include` inside the guard, or after the
+/// conditional holding it (or after the guard's `#define`), and closes before
+/// the guard's `#endif`, so included headers never get C linkage. Returns `None` for headers without
+/// a complete guard, and for headers that already have an `extern "C"` block
+/// or the block `linkage` generates.
+///
+/// @post `check_header` of the result reports no `MissingLinkageBlock` for `linkage`.
+pub fn add_linkage(text: &str, linkage: &Linkage) -> Option<String> {
+    let info = HeaderInfo::analyze(text);
+    let guard = info.guard?;
+    let (define_line, endif_line) = (guard.define_line?, guard.endif_line?);
+    // The block closes before the `#else` of a strict guard.
+    let endif_line = guard.else_line.unwrap_or(endif_line);
+    let lines = scan(text);
+    if info.has_linkage_block || lines.iter().any(|line| opens_linkage_block(line, linkage)) {
+        return None;
+    }
+
+    let inner = lines[guard.ifndef_line].depth + 1;
+    // A `#define` hidden from Doxygen sits in a conditional of its own; start after its `#endif`.
+    let after_define = match lines[define_line].depth {
+        depth if depth > inner => lines[define_line + 1..endif_line]
+            .iter()
+            .position(|line| line.is_directive("endif") && line.depth == depth)
+            .map_or(define_line, |offset| define_line + 1 + offset),
+        _ => define_line,
+    };
+    // The last line before the block.
+    let split = after_last_include(&lines[after_define + 1..endif_line], inner)
+        .map_or(after_define, |end| after_define + end);
+
+    let newline = match detect_line_endings(text) {
+        LineEndings::CRLF => "\r\n",
+        _ => "\n",
+    };
+    let join = |lines: &[ScannedLine<'_>]| lines.iter().map(|line| line.raw).collect::<String>();
+    let body = join(&lines[split + 1..endif_line]);
+    let body = body.trim_matches(|c| c == '\n' || c == '\r');
+
+    let mut out = join(&lines[..=split]);
+    if !out.ends_with('\n') {
+        out.push_str(newline);
+    }
+    linkage
+        .write_open(&mut out, newline)
+        .expect("writing to a String cannot fail");
+    out.push_str(newline);
+    if !body.is_empty() {
+        for line in body.lines() {
+            out.push_str(line);
+            out.push_str(newline);
+        }
+        out.push_str(newline);
+    }
+    linkage
+        .write_close(&mut out, newline)
+        .expect("writing to a String cannot fail");
+    out.push_str(&join(&lines[endif_line..]));
+    Some(out)
+}
+
+/// Rename the guard macro of a header, keeping everything else byte for byte.
+///
+/// @pre `existing` was obtained from `HeaderInfo::analyze(text)`.
+pub fn rename_guard(text: &str, existing: &ExistingGuard, new_name: &str) -> String {
+    let targets = [
+        Some(existing.ifndef_line),
+        existing.define_line,
+        existing.endif_line,
+    ];
+    text.split_inclusive('\n')
+        .enumerate()
+        .map(|(i, line)| {
+            if targets.contains(&Some(i)) {
+                replace_identifier(line, &existing.name, new_name)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect()
+}
+
+/// Replace whole-identifier occurrences of `from` in `line` with `to`.
+fn replace_identifier(line: &str, from: &str, to: &str) -> String {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut out = String::with_capacity(line.len() + to.len());
+    let mut rest = line;
+    while let Some(pos) = rest.find(from) {
+        let before_ok = !rest[..pos].ends_with(is_ident);
+        let after_ok = !rest[pos + from.len()..].starts_with(is_ident);
+        out.push_str(&rest[..pos]);
+        out.push_str(if before_ok && after_ok { to } else { from });
+        rest = &rest[pos + from.len()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Convert every line break in `text` to the newline of `line_ending`.
+pub fn convert_line_endings(text: &str, line_ending: LineEnding) -> String {
+    let newline = crate::render::newline(line_ending);
+    text.split_inclusive('\n')
+        .map(|line| {
+            let content = line.trim_end_matches('\n').trim_end_matches('\r');
+            if line.ends_with('\n') {
+                format!("{}{}", content, newline)
+            } else {
+                content.to_string()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    use wasm_bindgen_test::*;
+
+    const UUID_GUARD: &str = "ACME_0190F4C3_1A2B_7C3D_8E4F_5A6B7C8D9E0F_H";
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn analyze_finds_guard_after_banner() {
+        let text = format!(
+            "/* banner\n * text */\n// more\n\n#ifndef {0}\n#define {0}\n\n#if X\n#endif\nint f(void);\n#endif /* {0} */\n",
+            UUID_GUARD
+        );
+        let info = HeaderInfo::analyze(&text);
+        let guard = info.guard.unwrap();
+
+        assert_eq!(guard.name, UUID_GUARD);
+        assert_eq!(guard.ifndef_line, 4);
+        assert_eq!(guard.define_line, Some(5));
+        assert_eq!(guard.endif_line, Some(10));
+        assert_eq!(info.code_after_guard, None);
+        assert_eq!(info.line_endings, LineEndings::LF);
+        assert!(check_header(&text, &CheckOptions::default()).is_empty());
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn analyze_finds_the_alias_after_the_define() {
+        for hiding in [doxygen::GuardHiding::None, doxygen::GuardHiding::SkipThis] {
+            let mut guard = IncludeGuard::new(UUID_GUARD.to_string(), Language::C, LineEnding::LF);
+            guard.set_hiding(hiding);
+            guard.set_alias(Some("ACME_SOCKET_H_INCLUDED".to_string()));
+            let text = guard.wrap("int f(void);");
+
+            let existing = HeaderInfo::analyze(&text).guard.unwrap();
+            assert_eq!(existing.alias.as_deref(), Some("ACME_SOCKET_H_INCLUDED"));
+            assert!(check_header(&text, &CheckOptions::default()).is_empty());
+        }
+
+        let valued = format!(
+            "#ifndef {0}\n#define {0}\n#define VERSION 2\n#endif\n",
+            UUID_GUARD
+        );
+        assert_eq!(HeaderInfo::analyze(&valued).guard.unwrap().alias, None);
+        let feature = format!(
+            "#ifndef {0}\n#define {0}\n#define _GNU_SOURCE\n#endif\n",
+            UUID_GUARD
+        );
+        assert_eq!(HeaderInfo::analyze(&feature).guard.unwrap().alias, None);
+
+        let mut guard = IncludeGuard::new(UUID_GUARD.to_string(), Language::Cxx, LineEnding::LF);
+        guard.set_alias(Some("HAVE_SOCKET".to_string()));
+        let text = guard.render();
+        assert!(text.contains("#define HAVE_SOCKET // guardgen: alias\n"));
+        assert_eq!(
+            HeaderInfo::analyze(&text).guard.unwrap().alias.as_deref(),
+            Some("HAVE_SOCKET")
+        );
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn strict_guards_and_unguarded_markers() {
+        let mut guard = IncludeGuard::new(UUID_GUARD.to_string(), Language::None, LineEnding::LF);
+        guard.set_strict(true);
+        let text = guard.wrap("int f(void);");
+        assert_eq!(
+            text,
+            format!(
+                "#ifndef {0}\n#define {0}\n\nint f(void);\n\n\
+                 #else\n#error \"This header was included twice.\"\n#endif /* {0} */\n",
+                UUID_GUARD
+            )
+        );
+        let existing = HeaderInfo::analyze(&text).guard.unwrap();
+        assert_eq!(existing.else_line, Some(5));
+        assert_eq!(existing.endif_line, Some(7));
+        assert!(check_header(&text, &CheckOptions::default()).is_empty());
+
+        let linked = add_linkage(&text, &Linkage::begin_decls()).unwrap();
+        assert!(linked.contains("__END_DECLS\n\n#else\n"));
+
+        let options = CheckOptions {
+            language: Some(Language::C),
+            ..CheckOptions::default()
+        };
+        let fragment = format!("/* {} */\nCOLOR(red)\nCOLOR(green)\n", UNGUARDED_MARKER);
+        assert!(HeaderInfo::analyze(&fragment).unguarded);
+        assert!(check_header(&fragment, &options).is_empty());
+        let banner = format!(
+            "/*\n * Colors.\n * {}\n */\n\nCOLOR(red)\n",
+            UNGUARDED_MARKER
+        );
+        assert!(HeaderInfo::analyze(&banner).unguarded);
+        // A marker further down, e.g. in documentation, does not count.
+        let late = format!("int x;\n/* {} */\n", UNGUARDED_MARKER);
+        assert!(!HeaderInfo::analyze(&late).unguarded);
+        assert_eq!(
+            check_header("COLOR(red)\n", &options),
+            vec![HeaderIssue::MissingGuard, HeaderIssue::MissingLinkageBlock]
+        );
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn check_reports_structural_and_option_issues() {
+        assert_eq!(
+            check_header("int x;\n", &CheckOptions::default()),
+            vec![HeaderIssue::MissingGuard]
+        );
+        assert_eq!(
+            check_header(
+                "#if !defined(FOO_H)\n#define FOO_H\n#endif\nint x;\n",
+                &CheckOptions::default()
+            ),
+            vec![
+                HeaderIssue::CodeAfterGuard { line: 4 },
+                HeaderIssue::NotUuidGuard {
+                    guard: "FOO_H".to_string()
+                }
+            ]
+        );
+
+        let text = format!("#ifndef {0}\r\n#define {0}\r\n#endif\r\n", UUID_GUARD);
+        let options = CheckOptions {
+            prefix: Some("UUID".to_string()),
+            suffix: Some("H".to_string()),
+            uuid_kind: Some(UuidKind::V4),
+            language: Some(Language::C),
+            line_ending: Some(LineEnding::LF),
+            linkage: Linkage::ExternC,
+            audit_linkage: false,
+        };
+        assert_eq!(
+            check_header(&text, &options),
+            vec![
+                HeaderIssue::PrefixMismatch {
+                    expected: "UUID".to_string(),
+                    found: "ACME".to_string()
+                },
+                HeaderIssue::UuidVersionMismatch {
+                    expected: UuidKind::V4,
+                    found: Some(UuidKind::V7)
+                },
+                HeaderIssue::MissingLinkageBlock,
+                HeaderIssue::LineEndingMismatch {
+                    expected: LineEnding::LF,
+                    found: LineEndings::CRLF
+                },
+            ]
+        );
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn parse_guard_name_parts() {
+        let parts = GuardNameParts::parse(UUID_GUARD).unwrap();
+        assert_eq!(parts.prefix, "ACME");
+        assert_eq!(parts.uuid, "0190F4C3-1A2B-7C3D-8E4F-5A6B7C8D9E0F");
+        assert_eq!(parts.uuid_kind, Some(UuidKind::V7));
+        assert_eq!(parts.unix_millis, Some(0x0190_F4C3_1A2B));
+        assert_eq!(parts.suffix.as_deref(), Some("H"));
+
+        assert_eq!(GuardNameParts::parse("FOO_BAR_H"), None);
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn add_guard_keeps_banner_and_places_linkage_after_includes() {
+        let guard = IncludeGuard::new("G".to_string(), Language::C, LineEnding::LF);
+        let text = "// SPDX-License-Identifier: MIT\n\n#include <stddef.h>\n\nint f(void);\n";
+
+        assert_eq!(
+            add_guard(text, &guard),
+            "// SPDX-License-Identifier: MIT\n\
+             \n\
+             #ifndef G\n\
+             #define G\n\
+             \n\
+             #include <stddef.h>\n\
+             \n\
+             #ifdef __cplusplus\n\
+             extern \"C\" {\n\
+             #endif /* __cplusplus */\n\
+             \n\
+             int f(void);\n\
+             \n\
+             #ifdef __cplusplus\n\
+             } /* extern \"C\" */\n\
+             #endif /* __cplusplus */\n\
+             \n\
+             #endif /* G */\n"
+        );
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn add_guard_places_nullability_region_after_imports() {
+        let guard = IncludeGuard::new("G".to_string(), Language::ObjC, LineEnding::LF);
+        let text = "#import \"Base.h\"\n\n@interface A : Base\n@end\n";
+
+        assert_eq!(
+            add_guard(text, &guard),
+            "#ifndef G\n\
+             #define G\n\
+             \n\
+             #import \"Base.h\"\n\
+             \n\
+             #import <Foundation/Foundation.h>\n\
+             \n\
+             NS_ASSUME_NONNULL_BEGIN\n\
+             \n\
+             @interface A : Base\n\
+             @end\n\
+             \n\
+             NS_ASSUME_NONNULL_END\n\
+             \n\
+             #endif /* G */\n"
+        );
+
+        let cuda = IncludeGuard::new(UUID_GUARD.to_string(), Language::Cuda, LineEnding::LF);
+        let options = CheckOptions {
+            language: Some(Language::Cuda),
+            ..CheckOptions::default()
+        };
+        assert_eq!(check_header(&cuda.render(), &options), vec![]);
+        // CUDA is C++: templates must not end up in a linkage block.
+        let kernel = add_guard("#include <a.h>\ntemplate <class T> T f(T);\n", &cuda);
+        assert!(!kernel.contains("extern"));
+        assert_eq!(check_header(&kernel, &options), vec![]);
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn linkage_macros_are_added_and_recognised() {
+        let mut guard = IncludeGuard::new(UUID_GUARD.to_string(), Language::C, LineEnding::LF);
+        guard.set_linkage(Linkage::begin_decls());
+        let text = add_guard("#include <sys/cdefs.h>\nint f(void);\n", &guard);
+
+        assert_eq!(
+            text,
+            format!(
+                "#ifndef {0}\n#define {0}\n\n#include <sys/cdefs.h>\n\n__BEGIN_DECLS\n\n\
+                 int f(void);\n\n__END_DECLS\n\n#endif /* {0} */\n",
+                UUID_GUARD
+            )
+        );
+        let options = |linkage| CheckOptions {
+            language: Some(Language::C),
+            linkage,
+            ..CheckOptions::default()
+        };
+        assert!(check_header(&text, &options(Linkage::begin_decls())).is_empty());
+        assert_eq!(
+            check_header(&text, &options(Linkage::ExternC)),
+            vec![HeaderIssue::MissingLinkageBlock]
+        );
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn add_linkage_wraps_declarations_after_includes() {
+        let text = "#ifndef G\n#define G\n\n#include <stddef.h>\n#if X\n#include <x.h>\n#endif\n\nint f(void);\n\n#endif /* G */\n";
+        let linked = add_linkage(text, &Linkage::ExternC).unwrap();
+
+        assert_eq!(
+            linked,
+            "#ifndef G\n#define G\n\n#include <stddef.h>\n#if X\n#include <x.h>\n#endif\n\
+             \n\
+             #ifdef __cplusplus\n\
+             extern \"C\" {\n\
+             #endif /* __cplusplus */\n\
+             \n\
+             int f(void);\n\
+             \n\
+             #ifdef __cplusplus\n\
+             } /* extern \"C\" */\n\
+             #endif /* __cplusplus */\n\
+             \n\
+             #endif /* G */\n"
+        );
+        let includes_in_linkage = |text: &str| {
+            let audit = CheckOptions {
+                audit_linkage: true,
+                ..CheckOptions::default()
+            };
+            check_header(text, &audit)
+                .into_iter()
+                .filter(|issue| matches!(issue, HeaderIssue::IncludeInLinkage { .. }))
+                .count()
+        };
+        assert_eq!(includes_in_linkage(&linked), 0);
+        let nested = "#ifndef G\n#define G\n#ifdef A\n#if B\n#include <b.h>\n#endif\nint a(void);\n#endif\nint f(void);\n#endif\n";
+        let linked_nested = add_linkage(nested, &Linkage::ExternC).unwrap();
+        assert!(linked_nested.starts_with(
+            "#ifndef G\n#define G\n#ifdef A\n#if B\n#include <b.h>\n#endif\nint a(void);\n#endif\n\n#ifdef __cplusplus\n"
+        ));
+        assert_eq!(includes_in_linkage(&linked_nested), 0);
+        assert_eq!(add_linkage(&linked, &Linkage::begin_decls()), None);
+        assert_eq!(add_linkage("int f(void);\n", &Linkage::ExternC), None);
+        assert_eq!(
+            add_linkage(
+                "#ifndef G\r\n#define G\r\n#endif\r\n",
+                &Linkage::begin_decls()
+            )
+            .as_deref(),
+            Some(
+                "#ifndef G\r\n#define G\r\n\r\n__BEGIN_DECLS\r\n\r\n__END_DECLS\r\n\r\n#endif\r\n"
+            )
+        );
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn audit_reports_linkage_problems() {
+        let audit = |text: &str, linkage: Linkage| {
+            check_header(
+                text,
+                &CheckOptions {
+                    linkage,
+                    audit_linkage: true,
+                    ..CheckOptions::default()
+                },
+            )
+        };
+        let mut guard = IncludeGuard::new(UUID_GUARD.to_string(), Language::C, LineEnding::LF);
+        let body = "#include <stddef.h>\n#define MAX(a, b) \\\n  ((a) > (b) ? (a) : (b))\n\
+                    struct point { int x; int y; };\nDECLARE_HANDLE(h);\n\
+                    static inline int twice(int x) { return 2 * x; }\n\
+                    size_t point_count(const struct point *p,\n                   size_t n);\n";
+        let compliant = add_guard(body, &guard);
+        assert_eq!(audit(&compliant, Linkage::ExternC), vec![]);
+        guard.set_linkage(Linkage::begin_decls());
+        assert_eq!(
+            audit(&add_guard(body, &guard), Linkage::begin_decls()),
+            vec![]
+        );
+
+        assert_eq!(
+            audit(
+                &add_guard(
+                    body,
+                    &IncludeGuard::new(UUID_GUARD.to_string(), Language::None, LineEnding::LF)
+                ),
+                Linkage::ExternC
+            ),
+            vec![HeaderIssue::FunctionWithoutLinkage { line: 10 }]
+        );
+        assert_eq!(
+            audit(
+                &format!(
+                    "#ifndef {0}\n#define {0}\nextern \"C\" {{\n#include <a.h>\nint f(void);\n#endif\n",
+                    UUID_GUARD
+                ),
+                Linkage::ExternC
+            ),
+            vec![
+                HeaderIssue::UnbalancedLinkage { line: 3 },
+                HeaderIssue::IncludeInLinkage { line: 4 },
+            ]
+        );
+        assert_eq!(
+            audit(
+                &format!(
+                    "#ifndef {0}\n#define {0}\n__END_DECLS\n#endif\n",
+                    UUID_GUARD
+                ),
+                Linkage::begin_decls()
+            ),
+            vec![HeaderIssue::UnbalancedLinkage { line: 3 }]
+        );
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn rename_guard_touches_only_guard_lines() {
+        let text = "#ifndef OLD\n#define OLD\nint OLD_x;\n#endif // OLD\n";
+        let guard = HeaderInfo::analyze(text).guard.unwrap();
+
+        assert_eq!(
+            rename_guard(text, &guard, "NEW"),
+            "#ifndef NEW\n#define NEW\nint OLD_x;\n#endif // NEW\n"
+        );
+        assert_eq!(
+            convert_line_endings("a\r\nb\nc", LineEnding::CRLF),
+            "a\r\nb\r\nc"
+        );
+    }
+}
@@ -7,16 +7,65 @@ use wasm_bindgen::prelude::*;
 #[cfg(all(target_arch = "wasm32", target_os = "unknown", test))]
 use wasm_bindgen_test::*;
 
+pub mod alias;
+mod batch;
+pub mod detect;
+pub mod doxygen;
+pub mod enforce;
+mod guard;
+pub mod header;
+pub mod linkage;
+pub mod namespace;
+mod render;
+mod shared;
+mod source;
+pub mod spdx;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+mod state;
+pub mod template;
+mod v7;
+
+pub use batch::Guards;
+pub use guard::IncludeGuard;
+pub use shared::SharedIncludeGuardGenerator;
+pub use source::{Clock, FixedClock, OsRandom, RandomSource, SeededRng, SystemClock};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub use state::V7StateFile;
+
 /// Enum representing the target language.
 /// - `None`: No language-specific modifications.
 /// - `C`: Adds `extern "C"` for C compatibility.
 /// - `Cxx`: No additional modifications (C++ default behavior).
+/// - `ObjC`, `ObjCxx`: Imports Foundation and wraps the body in
+///   `NS_ASSUME_NONNULL_BEGIN`/`NS_ASSUME_NONNULL_END`.
+/// - `Cuda`: CUDA C++; no linkage block, since it would break templates.
+///   The language check of `enforce` requires `__CUDACC__`.
+/// - `OpenCl`: OpenCL C; no additional modifications, C comments.
+/// - `Glsl`, `Hlsl`: Shader includes; comments are `//`, which every shader preprocessor accepts.
 #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Language {
     None,
     C,
     Cxx,
+    ObjC,
+    ObjCxx,
+    Cuda,
+    OpenCl,
+    Glsl,
+    Hlsl,
+}
+
+impl Language {
+    /// Whether the language is a C++ dialect, i.e. has namespaces.
+    pub fn is_cxx(self) -> bool {
+        matches!(self, Language::Cxx | Language::ObjCxx | Language::Cuda)
+    }
+
+    /// Whether generated headers get a C linkage block.
+    pub fn has_c_linkage(self) -> bool {
+        self == Language::C
+    }
 }
 /// Provide a `Default` implementation delegating to `new()` to satisfy clippy.
 impl Default for IncludeGuardGenerator {
@@ -52,6 +101,72 @@ pub enum UuidKind {
     V4,
 }
 
+/// Enum selecting how UUID v7 orders values generated within one millisecond.
+///
+/// - Counter: A counter follows the millisecond timestamp (RFC 9562 method 1).
+/// - SubMillisecond: The 12 bits after the millisecond timestamp carry the
+///   sub-millisecond fraction of the clock (RFC 9562 method 3), followed by the counter.
+#[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum V7Ordering {
+    #[default]
+    Counter,
+    SubMillisecond,
+}
+
+/// Per-guard parameters for the batch APIs.
+///
+/// @post `GuardOptions::default()` matches the CLI defaults: prefix `UUID`,
+///       no suffix, no language-specific block, system line ending, UUID v7.
+#[cfg_attr(
+    all(target_arch = "wasm32", target_os = "unknown"),
+    wasm_bindgen(getter_with_clone)
+)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GuardOptions {
+    pub prefix: String,
+    pub suffix: Option<String>,
+    pub language: Language,
+    pub line_ending: LineEnding,
+    pub uuid_kind: UuidKind,
+}
+
+#[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen)]
+impl GuardOptions {
+    /// Create options from every parameter that `generate` takes.
+    #[cfg_attr(
+        all(target_arch = "wasm32", target_os = "unknown"),
+        wasm_bindgen(constructor)
+    )]
+    pub fn new(
+        prefix: String,
+        suffix: Option<String>,
+        language: Language,
+        line_ending: LineEnding,
+        uuid_kind: UuidKind,
+    ) -> Self {
+        GuardOptions {
+            prefix,
+            suffix,
+            language,
+            line_ending,
+            uuid_kind,
+        }
+    }
+}
+
+impl Default for GuardOptions {
+    fn default() -> Self {
+        GuardOptions::new(
+            "UUID".to_string(),
+            None,
+            Language::None,
+            LineEnding::None,
+            UuidKind::V7,
+        )
+    }
+}
+
 /// Include guard generator struct.
 ///
 /// @pre The `prefix` must be a non-empty string describing the guard prefix.
@@ -64,6 +179,19 @@ pub struct IncludeGuardGenerator {
     // Store the official `ContextV7` from the `uuid` crate so we can rely on its
     // reseeding/monotonic behaviour instead of rolling our own counter logic.
     v7_context: Option<uuid::ContextV7>,
+    // Time source for UUID v7 timestamps; the system clock unless injected.
+    clock: Box<dyn Clock + Send>,
+    // Source of the random bits when injected (seeded or caller-supplied).
+    // `None` uses the `uuid` crate's own randomness.
+    rng: Option<Box<dyn RandomSource + Send>>,
+    // How v7 UUIDs generated within one millisecond are ordered.
+    v7_ordering: V7Ordering,
+    // Last (millis, counter) pair issued by our own v7 counter, used when
+    // `v7_context` cannot provide the requested ordering.
+    v7_last: Option<(u64, u64)>,
+    // Optional file shared between processes that orders v7 UUIDs machine-wide.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    state_file: Option<V7StateFile>,
 }
 
 #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen)]
@@ -84,9 +212,54 @@ impl IncludeGuardGenerator {
     pub fn new() -> Self {
         IncludeGuardGenerator {
             v7_context: Some(uuid::ContextV7::new()),
+            clock: Box::new(SystemClock),
+            rng: None,
+            v7_ordering: V7Ordering::Counter,
+            v7_last: None,
+            #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+            state_file: None,
         }
     }
 
+    /// Use a fixed Unix timestamp instead of the wall clock for UUID v7 generation.
+    ///
+    /// @post Subsequent v7 guards carry `seconds` in their timestamp field.
+    ///
+    /// This is intended for reproducible builds honouring `SOURCE_DATE_EPOCH`.
+    #[cfg_attr(
+        all(target_arch = "wasm32", target_os = "unknown"),
+        wasm_bindgen(js_name = setTimestamp)
+    )]
+    pub fn set_timestamp(&mut self, seconds: u64) {
+        self.clock = Box::new(FixedClock::new(seconds, 0));
+    }
+
+    /// Draw the random bits of generated UUIDs from a generator seeded with `seed`.
+    ///
+    /// @post Two generators with the same seed and timestamp produce identical guards
+    ///       for the same sequence of `generate` calls.
+    #[cfg_attr(
+        all(target_arch = "wasm32", target_os = "unknown"),
+        wasm_bindgen(js_name = setSeed)
+    )]
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Some(Box::new(SeededRng::new(seed)));
+    }
+
+    /// Select how v7 UUIDs generated within one millisecond are ordered.
+    ///
+    /// @post Subsequent v7 guards follow `ordering`.
+    ///
+    /// `SubMillisecond` uses the generator's own counter even with the default
+    /// randomness, since `ContextV7`'s precision mode does not keep the fraction intact.
+    #[cfg_attr(
+        all(target_arch = "wasm32", target_os = "unknown"),
+        wasm_bindgen(js_name = setV7Ordering)
+    )]
+    pub fn set_v7_ordering(&mut self, ordering: V7Ordering) {
+        self.v7_ordering = ordering;
+    }
+
     /// Generate the include guard string using the generator's configuration.
     ///
     /// @pre The generator was created via `new`.
@@ -103,76 +276,206 @@ impl IncludeGuardGenerator {
         line_ending: LineEnding,
         uuid_kind: UuidKind,
     ) -> String {
-        // Generate a UUID string according to the selected kind.
-        let uuid_string = match uuid_kind {
-            UuidKind::V4 => uuid::Uuid::new_v4().to_string(),
-            UuidKind::V7 => {
+        self.try_generate(prefix, suffix, language, line_ending, uuid_kind)
+            .expect("failed to update the UUID v7 state file")
+    }
+}
+
+impl IncludeGuardGenerator {
+    /// Generate the include guard string, reporting state-file failures.
+    ///
+    /// @post Identical to `generate`, except that an I/O error while updating a
+    ///       configured v7 state file is returned instead of panicking.
+    pub fn try_generate(
+        &mut self,
+        prefix: String,
+        suffix: Option<String>,
+        language: Language,
+        line_ending: LineEnding,
+        uuid_kind: UuidKind,
+    ) -> std::io::Result<String> {
+        let uuid = self.next_uuid(uuid_kind)?;
+        Ok(render::render_guard(
+            &uuid,
+            &prefix,
+            suffix.as_deref(),
+            language,
+            line_ending,
+        ))
+    }
+
+    /// Generate the next include guard as a value whose parts can surround existing content.
+    ///
+    /// @post `try_next_guard(..)?.render()` equals what `try_generate` would return.
+    pub fn try_next_guard(
+        &mut self,
+        prefix: &str,
+        suffix: Option<&str>,
+        language: Language,
+        line_ending: LineEnding,
+        uuid_kind: UuidKind,
+    ) -> std::io::Result<IncludeGuard> {
+        let uuid = self.next_uuid(uuid_kind)?;
+        let name = render::guard_name(&uuid, prefix, suffix);
+        Ok(IncludeGuard::new(name, language, line_ending))
+    }
+
+    /// Generate an include guard and write it into `out` without intermediate allocations.
+    ///
+    /// @post On success `out` received exactly the text `try_generate` would return.
+    ///
+    /// Formatting failures of `out` are reported as `io::ErrorKind::Other`, like
+    /// `io::Write::write_fmt` does.
+    pub fn write_guard<W: std::fmt::Write + ?Sized>(
+        &mut self,
+        out: &mut W,
+        prefix: &str,
+        suffix: Option<&str>,
+        language: Language,
+        line_ending: LineEnding,
+        uuid_kind: UuidKind,
+    ) -> std::io::Result<()> {
+        let uuid = self.next_uuid(uuid_kind)?;
+        render::write_guard(out, &uuid, prefix, suffix, language, line_ending)
+            .map_err(|_| std::io::Error::other("formatter error"))
+    }
+
+    /// Generate an include guard and write it straight into the byte sink `out`.
+    ///
+    /// @post On success `out` received exactly the bytes of the `try_generate` text.
+    pub fn write_guard_io<W: std::io::Write + ?Sized>(
+        &mut self,
+        out: &mut W,
+        prefix: &str,
+        suffix: Option<&str>,
+        language: Language,
+        line_ending: LineEnding,
+        uuid_kind: UuidKind,
+    ) -> std::io::Result<()> {
+        let uuid = self.next_uuid(uuid_kind)?;
+        let mut adapter = render::IoAdapter::new(out);
+        let result =
+            render::write_guard(&mut adapter, &uuid, prefix, suffix, language, line_ending);
+        adapter.finish(result)
+    }
+
+    /// Produce the next UUID of the requested kind from the configured sources.
+    pub(crate) fn next_uuid(&mut self, uuid_kind: UuidKind) -> std::io::Result<uuid::Uuid> {
+        let (seconds, nanos) = self.clock.now();
+
+        if uuid_kind == UuidKind::V7 && self.uses_own_v7_counter() {
+            // Our own 42-bit counter decides the ordering (in-process, or through
+            // the state file across processes); only the trailing bits stay random.
+            let mut bytes = [0u8; 10];
+            match self.rng.as_mut() {
+                Some(rng) => rng.fill_bytes(&mut bytes),
+                None => OsRandom.fill_bytes(&mut bytes),
+            }
+            let random = u64::from_be_bytes([
+                bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
+            ]);
+            let (start, floor) = v7::counter_bounds(self.v7_ordering, nanos, random);
+            let (millis, counter) =
+                self.reserve_v7(v7::unix_millis(seconds, nanos), start, floor)?;
+            return Ok(v7::encode(millis, counter, &mut bytes));
+        }
+
+        let uuid = match (uuid_kind, self.rng.as_mut()) {
+            (UuidKind::V4, None) => uuid::Uuid::new_v4(),
+            (UuidKind::V4, Some(rng)) => {
+                let mut bytes = [0u8; 16];
+                rng.fill_bytes(&mut bytes);
+                uuid::Builder::from_random_bytes(bytes).into_uuid()
+            }
+            (UuidKind::V7, _) => {
+                // Only reached without an injected RNG (see `uses_own_v7_counter`).
                 // Use the crate-provided ContextV7 to produce a Timestamp that
                 // carries a proper counter; this avoids the previous manual
                 // counter arithmetic and follows the crate's reseeding/monotonic logic.
-                let (seconds, nanos) = unix_time();
-
                 let ts = match self.v7_context.as_ref() {
                     Some(ctx) => uuid::Timestamp::from_unix(ctx, seconds, nanos),
                     None => uuid::Timestamp::from_unix(uuid::NoContext, seconds, nanos),
                 };
 
-                uuid::Uuid::new_v7(ts).to_string()
+                uuid::Uuid::new_v7(ts)
             }
         };
+        Ok(uuid)
+    }
 
-        // Format guard pieces and return the assembled include-guard text.
-        let uuid = uuid_string.replace('-', "_").to_uppercase();
-        let mut guard = vec![prefix, uuid.clone()];
+    /// Whether v7 UUIDs come from our own counter rather than `v7_context`.
+    ///
+    /// ContextV7 seeds its counter from the crate's global RNG, so an injected
+    /// source (`--seed`) also goes through our counter to stay monotonic.
+    fn uses_own_v7_counter(&self) -> bool {
+        if self.rng.is_some() {
+            return true;
+        }
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        if self.state_file.is_some() {
+            return true;
+        }
+        self.v7_ordering == V7Ordering::SubMillisecond
+    }
 
-        // If a suffix was provided, append it to the guard components.
-        if let Some(s) = &suffix {
-            guard.push(s.clone());
+    /// Reserve the next v7 `(millis, counter)` pair, through the state file if configured.
+    fn reserve_v7(&mut self, millis: u64, start: u64, floor: u64) -> std::io::Result<(u64, u64)> {
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        if let Some(state_file) = self.state_file.as_ref() {
+            return state_file.reserve(millis, start, floor);
         }
+        let next = v7::advance(self.v7_last, millis, start, floor);
+        self.v7_last = Some(next);
+        Ok(next)
+    }
 
-        let guard = guard.join("_");
-
-        let ifndef = format!("#ifndef {}", guard);
-        let define = format!("#define {}", guard);
-        let endif = format!("#endif /* {} */", guard);
-
-        let mut text = vec![ifndef, define];
-
-        // If the target language is C, add extern "C" compatibility blocks.
-        // This branch ensures C consumers get the correct linkage annotations.
-        if let Language::C = language {
-            let extern_c: Vec<String> = vec![
-                "".to_string(), // blank line
-                "#ifdef __cplusplus".to_string(),
-                "extern \"C\" {".to_string(),
-                "#endif /* __cplusplus */".to_string(),
-                "".to_string(), // blank line
-                "#ifdef __cplusplus".to_string(),
-                "} /* extern \"C\" */".to_string(),
-                "#endif /* __cplusplus */".to_string(),
-                "".to_string(), // blank line
-            ];
-            text.extend(extern_c);
+    /// Create a generator reading time from `clock` and random bits from `rng`.
+    ///
+    /// @post With a deterministic clock and RNG (e.g. `FixedClock` and `SeededRng`)
+    ///       the sequence of generated guards is exactly predictable.
+    ///
+    /// Not exported to wasm; use `setTimestamp`/`setSeed` there instead.
+    pub fn with_sources<C, R>(clock: C, rng: R) -> Self
+    where
+        C: Clock + Send + 'static,
+        R: RandomSource + Send + 'static,
+    {
+        IncludeGuardGenerator {
+            v7_context: Some(uuid::ContextV7::new()),
+            clock: Box::new(clock),
+            rng: Some(Box::new(rng)),
+            v7_ordering: V7Ordering::Counter,
+            v7_last: None,
+            #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+            state_file: None,
         }
+    }
 
-        text.push(endif);
-        text.push("".to_string());
-
-        let newline = match line_ending {
-            LineEnding::LF => "\n",
-            LineEnding::CRLF => "\r\n",
-            LineEnding::None => {
-                // Qualitative explanation: pick system default line ending.
-                if cfg!(target_os = "windows") {
-                    "\r\n"
-                } else {
-                    "\n"
-                }
-            }
+    /// Create a generator reading time from `clock` while keeping the default randomness.
+    ///
+    /// @post UUID v7 guards stay monotonic per generator, as with `new`.
+    pub fn with_clock<C>(clock: C) -> Self
+    where
+        C: Clock + Send + 'static,
+    {
+        IncludeGuardGenerator {
+            v7_context: Some(uuid::ContextV7::new()),
+            clock: Box::new(clock),
+            rng: None,
+            v7_ordering: V7Ordering::Counter,
+            v7_last: None,
+            #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+            state_file: None,
         }
-        .to_string();
+    }
 
-        text.join(&newline)
+    /// Order v7 UUIDs through `state_file` so that they increase across processes.
+    ///
+    /// @post Every v7 guard generated afterwards, by this or any other process
+    ///       using the same file, is strictly greater than all earlier ones.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn set_state_file(&mut self, state_file: V7StateFile) {
+        self.state_file = Some(state_file);
     }
 }
 
@@ -198,28 +501,6 @@ pub fn generate_guard(
     generator.generate(prefix, suffix, x, line_ending, UuidKind::V7)
 }
 
-fn unix_time() -> (u64, u32) {
-    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
-    {
-        // `js_sys::Date::now()` returns milliseconds since the epoch as an `f64`.
-        // Convert to integer milliseconds, then split into seconds and nanoseconds.
-        let unix_ms = js_sys::Date::now().floor() as u64;
-        let seconds = unix_ms / 1000u64;
-        let nanos = ((unix_ms % 1000) as u32) * 1_000_000u32;
-        (seconds, nanos)
-    }
-    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
-    {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .expect("Time went backwards");
-        let seconds = now.as_secs();
-        // `subsec_millis()` returns the subsecond part in milliseconds; convert to nanoseconds.
-        let nanos = now.subsec_millis() * 1_000_000;
-        (seconds, nanos)
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -228,7 +509,7 @@ mod tests {
     #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
     wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
 
-    fn extract_uuids(text: &str) -> Vec<String> {
+    pub(crate) fn extract_uuids(text: &str) -> Vec<String> {
         let re =
             Regex::new(r"[0-9A-F]{8}_[0-9A-F]{4}_[0-9A-F]{4}_[0-9A-F]{4}_[0-9A-F]{12}").unwrap();
 
@@ -297,6 +578,68 @@ mod tests {
         assert!(result.contains("} /* extern \"C\" */"));
     }
 
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn test_generate_guard_for_other_languages() {
+        let cuda = generate_guard("TEST".to_string(), None, Language::Cuda, LineEnding::LF);
+        assert!(!cuda.contains("extern"));
+
+        let objc = generate_guard("TEST".to_string(), None, Language::ObjC, LineEnding::LF);
+        assert!(objc.contains("#import <Foundation/Foundation.h>\n\nNS_ASSUME_NONNULL_BEGIN\n\n"));
+        assert!(objc.contains("NS_ASSUME_NONNULL_END\n\n#endif /* TEST_"));
+
+        let glsl = generate_guard("TEST".to_string(), None, Language::Glsl, LineEnding::LF);
+        let uuid = &extract_uuids(glsl.as_str())[0];
+        assert!(glsl.ends_with(format!("#define TEST_{0}\n#endif // TEST_{0}\n", uuid).as_str()));
+
+        let opencl = generate_guard("TEST".to_string(), None, Language::OpenCl, LineEnding::LF);
+        assert!(opencl.ends_with(" */\n"));
+        assert!(!opencl.contains("extern"));
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn test_assembler_safe_guard_hides_c_parts() {
+        let mut guard = IncludeGuard::new("G".to_string(), Language::C, LineEnding::LF);
+        guard.set_assembler_safe(true);
+
+        assert_eq!(
+            guard.wrap("int f(void);"),
+            "#ifndef G\n\
+             #define G\n\
+             \n\
+             #ifndef __ASSEMBLER__\n\
+             \n\
+             #ifdef __cplusplus\n\
+             extern \"C\" {\n\
+             #endif /* __cplusplus */\n\
+             \n\
+             int f(void);\n\
+             \n\
+             #ifdef __cplusplus\n\
+             } /* extern \"C\" */\n\
+             #endif /* __cplusplus */\n\
+             \n\
+             #endif /* __ASSEMBLER__ */\n\
+             \n\
+             #endif /* G */\n"
+        );
+        assert_eq!(guard.render(), guard.opening() + "\n" + &guard.closing());
+
+        let mut guard = IncludeGuard::new("G".to_string(), Language::None, LineEnding::LF);
+        guard.set_assembler_safe(true);
+        assert_eq!(
+            guard.render(),
+            "#ifndef G\n#define G\n\n#ifndef __ASSEMBLER__\n\n#endif /* __ASSEMBLER__ */\n\n#endif /* G */\n"
+        );
+        assert_eq!(
+            header::check_header(&guard.wrap("int x;"), &Default::default()),
+            vec![header::HeaderIssue::NotUuidGuard {
+                guard: "G".to_string()
+            }]
+        );
+    }
+
     #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
     #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
     fn test_include_guard_generator_v7_uniqueness() {
@@ -359,4 +702,285 @@ mod tests {
             assert!(seen_v7.insert(u), "Duplicate UUID found for v7 generator");
         }
     }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn test_include_guard_generator_reproducible_with_timestamp_and_seed() {
+        let generate_pair = |uuid_kind| {
+            let mut generator = IncludeGuardGenerator::new();
+            generator.set_timestamp(1_700_000_000);
+            generator.set_seed(42);
+            let first = generator.generate(
+                "TEST".to_string(),
+                None,
+                Language::None,
+                LineEnding::LF,
+                uuid_kind,
+            );
+            let second = generator.generate(
+                "TEST".to_string(),
+                None,
+                Language::None,
+                LineEnding::LF,
+                uuid_kind,
+            );
+            (first, second)
+        };
+
+        for uuid_kind in [UuidKind::V7, UuidKind::V4] {
+            let (a1, a2) = generate_pair(uuid_kind);
+            let (b1, b2) = generate_pair(uuid_kind);
+
+            // Same inputs give byte-identical output, while successive calls still differ.
+            assert_eq!(a1, b1);
+            assert_eq!(a2, b2);
+            assert_ne!(a1, a2);
+        }
+
+        // The v7 timestamp field carries the fixed time (1_700_000_000_000 ms).
+        let (v7, _) = generate_pair(UuidKind::V7);
+        let uuid = &extract_uuids(v7.as_str())[0];
+        assert!(uuid.starts_with("018BCFE5_6800_7"));
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn test_include_guard_generator_with_injected_sources_snapshot() {
+        let mut generator = IncludeGuardGenerator::with_sources(
+            FixedClock::new(1_700_000_000, 0),
+            |dest: &mut [u8]| dest.fill(0xAB),
+        );
+
+        let v7 = generator.generate(
+            "TEST".to_string(),
+            Some("H".to_string()),
+            Language::None,
+            LineEnding::LF,
+            UuidKind::V7,
+        );
+        // The counter is reseeded from the random bits with its top bit clear.
+        assert_eq!(
+            v7,
+            "#ifndef TEST_018BCFE5_6800_76AE_ABAB_ABABABABABAB_H\n\
+             #define TEST_018BCFE5_6800_76AE_ABAB_ABABABABABAB_H\n\
+             #endif /* TEST_018BCFE5_6800_76AE_ABAB_ABABABABABAB_H */\n"
+        );
+        // Within the same fixed millisecond the counter keeps the order.
+        let next = generator.generate(
+            "TEST".to_string(),
+            Some("H".to_string()),
+            Language::None,
+            LineEnding::LF,
+            UuidKind::V7,
+        );
+        assert!(extract_uuids(next.as_str())[0] > extract_uuids(v7.as_str())[0]);
+
+        let v4 = generator.generate(
+            "TEST".to_string(),
+            None,
+            Language::None,
+            LineEnding::LF,
+            UuidKind::V4,
+        );
+        assert_eq!(
+            extract_uuids(v4.as_str())[0],
+            "ABABABAB_ABAB_4BAB_ABAB_ABABABABABAB"
+        );
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn test_include_guard_generator_with_stepping_clock_closure() {
+        let mut millis = 1_700_000_000_000u64;
+        let clock = move || {
+            millis += 1;
+            (millis / 1000, ((millis % 1000) as u32) * 1_000_000)
+        };
+        let mut generator = IncludeGuardGenerator::with_sources(clock, SeededRng::new(7));
+
+        let first = generator.generate(
+            "TEST".to_string(),
+            None,
+            Language::None,
+            LineEnding::LF,
+            UuidKind::V7,
+        );
+        let second = generator.generate(
+            "TEST".to_string(),
+            None,
+            Language::None,
+            LineEnding::LF,
+            UuidKind::V7,
+        );
+
+        assert!(extract_uuids(first.as_str())[0].starts_with("018BCFE5_6801_7"));
+        assert!(extract_uuids(second.as_str())[0].starts_with("018BCFE5_6802_7"));
+    }
+
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    #[test]
+    fn test_include_guard_generator_state_file_orders_across_generators() {
+        let path =
+            std::env::temp_dir().join(format!("guardgen-lib-state-test-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        // Separate generators stand in for separate processes sharing one file,
+        // all reading the same frozen clock.
+        let mut previous = String::new();
+        for _ in 0..4 {
+            let mut generator =
+                IncludeGuardGenerator::with_clock(FixedClock::new(1_700_000_000, 0));
+            generator.set_state_file(V7StateFile::open(&path).unwrap());
+            let text = generator
+                .try_generate(
+                    "TEST".to_string(),
+                    None,
+                    Language::None,
+                    LineEnding::LF,
+                    UuidKind::V7,
+                )
+                .unwrap();
+            let uuid = extract_uuids(text.as_str())[0].clone();
+            assert!(uuid.starts_with("018BCFE5_6800_7"));
+            assert!(uuid > previous, "{} is not greater than {}", uuid, previous);
+            previous = uuid;
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn test_include_guard_generator_sub_millisecond_ordering() {
+        // 0.5 ms past the second maps to a 12-bit fraction of 2048 (0x800).
+        let mut generator = IncludeGuardGenerator::with_sources(
+            FixedClock::new(1_700_000_000, 500_000),
+            |dest: &mut [u8]| dest.fill(0),
+        );
+        generator.set_v7_ordering(V7Ordering::SubMillisecond);
+
+        let text = generator.generate(
+            "TEST".to_string(),
+            None,
+            Language::None,
+            LineEnding::LF,
+            UuidKind::V7,
+        );
+        assert_eq!(
+            extract_uuids(text.as_str())[0],
+            "018BCFE5_6800_7800_8000_000000000000"
+        );
+
+        // With the default randomness, later sub-millisecond readings sort later.
+        let mut nanos = 0u32;
+        let clock = move || {
+            nanos += 250_000;
+            (1_700_000_000, nanos)
+        };
+        let mut generator = IncludeGuardGenerator::with_clock(clock);
+        generator.set_v7_ordering(V7Ordering::SubMillisecond);
+        let uuids: Vec<String> = (0..3)
+            .map(|_| {
+                let text = generator.generate(
+                    "TEST".to_string(),
+                    None,
+                    Language::None,
+                    LineEnding::LF,
+                    UuidKind::V7,
+                );
+                extract_uuids(text.as_str())[0].clone()
+            })
+            .collect();
+        assert!(uuids[0].starts_with("018BCFE5_6800_7400"));
+        assert!(uuids[1].starts_with("018BCFE5_6800_7800"));
+        assert!(uuids[2].starts_with("018BCFE5_6800_7C00"));
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn test_write_guard_matches_generate() {
+        for language in [
+            Language::None,
+            Language::C,
+            Language::Cxx,
+            Language::ObjC,
+            Language::Cuda,
+            Language::Hlsl,
+        ] {
+            for line_ending in [LineEnding::LF, LineEnding::CRLF] {
+                let mut generator = IncludeGuardGenerator::with_sources(
+                    FixedClock::new(1_700_000_000, 0),
+                    SeededRng::new(3),
+                );
+                let expected = generator.generate(
+                    "TEST".to_string(),
+                    Some("H".to_string()),
+                    language,
+                    line_ending,
+                    UuidKind::V4,
+                );
+
+                let mut generator = IncludeGuardGenerator::with_sources(
+                    FixedClock::new(1_700_000_000, 0),
+                    SeededRng::new(3),
+                );
+                let mut text = String::new();
+                generator
+                    .write_guard(
+                        &mut text,
+                        "TEST",
+                        Some("H"),
+                        language,
+                        line_ending,
+                        UuidKind::V4,
+                    )
+                    .unwrap();
+                assert_eq!(text, expected);
+
+                let mut generator = IncludeGuardGenerator::with_sources(
+                    FixedClock::new(1_700_000_000, 0),
+                    SeededRng::new(3),
+                );
+                let mut bytes = Vec::new();
+                generator
+                    .write_guard_io(
+                        &mut bytes,
+                        "TEST",
+                        Some("H"),
+                        language,
+                        line_ending,
+                        UuidKind::V4,
+                    )
+                    .unwrap();
+                assert_eq!(bytes, expected.as_bytes());
+            }
+        }
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn test_next_guard_renders_like_generate() {
+        for language in [Language::None, Language::C, Language::Cxx] {
+            let mut generator = IncludeGuardGenerator::with_sources(
+                FixedClock::new(1_700_000_000, 0),
+                SeededRng::new(5),
+            );
+            let expected = generator.generate(
+                "TEST".to_string(),
+                None,
+                language,
+                LineEnding::LF,
+                UuidKind::V7,
+            );
+
+            let mut generator = IncludeGuardGenerator::with_sources(
+                FixedClock::new(1_700_000_000, 0),
+                SeededRng::new(5),
+            );
+            let guard = generator
+                .try_next_guard("TEST", None, language, LineEnding::LF, UuidKind::V7)
+                .unwrap();
+            assert_eq!(guard.render(), expected);
+        }
+    }
 }
@@ -0,0 +1,131 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! The C linkage block placed inside guards for `Language::C`.
+
+use std::fmt;
+
+/// Lines opening the `extern "C"` block emitted after `#define` for `Language::C`.
+const EXTERN_C_OPEN_LINES: [&str; 4] = [
+    "", // blank line
+    "#ifdef __cplusplus",
+    "extern \"C\" {",
+    "#endif /* __cplusplus */",
+];
+
+/// Lines closing the `extern "C"` block emitted before `#endif` for `Language::C`.
+const EXTERN_C_CLOSE_LINES: [&str; 4] = [
+    "#ifdef __cplusplus",
+    "} /* extern \"C\" */",
+    "#endif /* __cplusplus */",
+    "", // blank line
+];
+
+/// How C declarations get C linkage when the header is compiled as C++.
+/// - `ExternC`: The literal `#ifdef __cplusplus` / `extern "C" {` block.
+/// - `Macros`: A pair of project macros such as `__BEGIN_DECLS`/`__END_DECLS`
+///   that expand to that block.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Linkage {
+    #[default]
+    ExternC,
+    Macros {
+        begin: String,
+        end: String,
+    },
+}
+
+impl Linkage {
+    /// The glibc/BSD `<sys/cdefs.h>` pair.
+    pub fn begin_decls() -> Self {
+        Linkage::macros("__BEGIN_DECLS", "__END_DECLS")
+    }
+
+    /// A begin/end macro pair.
+    ///
+    /// @pre `begin` and `end` are valid preprocessor identifiers.
+    pub fn macros(begin: &str, end: &str) -> Self {
+        Linkage::Macros {
+            begin: begin.to_string(),
+            end: end.to_string(),
+        }
+    }
+
+    /// Write the opening half of the block, starting with a blank line.
+    pub(crate) fn write_open<W: fmt::Write + ?Sized>(
+        &self,
+        out: &mut W,
+        newline: &str,
+    ) -> fmt::Result {
+        match self {
+            Linkage::ExternC => {
+                for line in EXTERN_C_OPEN_LINES {
+                    out.write_str(line)?;
+                    out.write_str(newline)?;
+                }
+                Ok(())
+            }
+            Linkage::Macros { begin, .. } => write!(out, "{}{}{}", newline, begin, newline),
+        }
+    }
+
+    /// Write the closing half of the block, ending with a blank line.
+    pub(crate) fn write_close<W: fmt::Write + ?Sized>(
+        &self,
+        out: &mut W,
+        newline: &str,
+    ) -> fmt::Result {
+        match self {
+            Linkage::ExternC => {
+                for line in EXTERN_C_CLOSE_LINES {
+                    out.write_str(line)?;
+                    out.write_str(newline)?;
+                }
+                Ok(())
+            }
+            Linkage::Macros { end, .. } => write!(out, "{}{}{}", end, newline, newline),
+        }
+    }
+
+    /// Whether `code` (a line stripped of comments) opens this block.
+    pub(crate) fn opens(&self, code: &str) -> bool {
+        match self {
+            Linkage::ExternC => code
+                .strip_prefix("extern")
+                .map(str::trim_start)
+                .and_then(|rest| rest.strip_prefix("\"C\""))
+                .is_some_and(|rest| rest.trim_start().starts_with('{')),
+            Linkage::Macros { begin, .. } => code.trim_end_matches(';').trim_end() == begin,
+        }
+    }
+
+    /// Whether `code` closes a macro block. The `extern "C"` block closes with its brace.
+    pub(crate) fn closes(&self, code: &str) -> bool {
+        match self {
+            Linkage::ExternC => false,
+            Linkage::Macros { end, .. } => code.trim_end_matches(';').trim_end() == end,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn macro_pairs_replace_the_extern_c_block() {
+        let linkage = Linkage::begin_decls();
+        let mut text = String::new();
+        linkage.write_open(&mut text, "\n").unwrap();
+        linkage.write_close(&mut text, "\n").unwrap();
+
+        assert_eq!(text, "\n__BEGIN_DECLS\n__END_DECLS\n\n");
+        assert!(linkage.opens("__BEGIN_DECLS"));
+        assert!(!linkage.opens("extern \"C\" {"));
+        assert!(Linkage::ExternC.opens("extern \"C\" {"));
+    }
+}
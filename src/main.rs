@@ -1,20 +1,36 @@
 // SPDX-FileCopyrightText: 2025 Daisuke Nagao
 // SPDX-License-Identifier: MIT
 
-use clap::{Parser, ValueEnum};
-use std::fs;
-use std::fs::OpenOptions;
-use std::io::Write;
+use clap::{ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+mod commands;
+mod config;
 
 /// Enum representing the target language.
 /// - `None`: No language-specific modifications.
 /// - `C`: Adds `extern "C"` for C compatibility.
 /// - `Cxx`: No additional modifications (C++ default behavior).
-#[derive(Clone, Debug, ValueEnum)]
+/// - `ObjC`, `ObjCxx`: Objective-C and Objective-C++ headers.
+/// - `Cuda`, `OpenCl`: GPU kernel headers.
+/// - `Glsl`, `Hlsl`: Shader includes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
 enum Language {
     None,
     C,
     Cxx,
+    #[value(name = "objc")]
+    ObjC,
+    #[value(name = "objcxx", alias = "objc++")]
+    #[serde(alias = "objc++")]
+    ObjCxx,
+    Cuda,
+    #[value(name = "opencl")]
+    OpenCl,
+    Glsl,
+    Hlsl,
 }
 
 impl From<Language> for guardgen_lib::Language {
@@ -23,6 +39,12 @@ impl From<Language> for guardgen_lib::Language {
             Language::None => guardgen_lib::Language::None,
             Language::C => guardgen_lib::Language::C,
             Language::Cxx => guardgen_lib::Language::Cxx,
+            Language::ObjC => guardgen_lib::Language::ObjC,
+            Language::ObjCxx => guardgen_lib::Language::ObjCxx,
+            Language::Cuda => guardgen_lib::Language::Cuda,
+            Language::OpenCl => guardgen_lib::Language::OpenCl,
+            Language::Glsl => guardgen_lib::Language::Glsl,
+            Language::Hlsl => guardgen_lib::Language::Hlsl,
         }
     }
 }
@@ -32,7 +54,7 @@ impl From<Language> for guardgen_lib::Language {
 /// - `None`: Uses system default.
 /// - `LF`: Uses Unix-style LF.
 /// - `CRLF`: Uses Windows-style CRLF.
-#[derive(Clone, Debug, ValueEnum)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
 enum LineEnding {
     None,
     LF,
@@ -67,7 +89,98 @@ impl From<UuidVersion> for guardgen_lib::UuidKind {
     }
 }
 
+/// Enum selecting how UUID v7 orders guards generated within one millisecond.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum V7Ordering {
+    Counter,
+    #[value(alias = "precision")]
+    SubMillisecond,
+}
+
+impl From<V7Ordering> for guardgen_lib::V7Ordering {
+    fn from(val: V7Ordering) -> Self {
+        match val {
+            V7Ordering::Counter => guardgen_lib::V7Ordering::Counter,
+            V7Ordering::SubMillisecond => guardgen_lib::V7Ordering::SubMillisecond,
+        }
+    }
+}
+
+/// Enum selecting how guard definitions are hidden from Doxygen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum HideGuard {
+    Cond,
+    #[value(alias = "skip")]
+    SkipThis,
+}
+
+impl From<HideGuard> for guardgen_lib::doxygen::GuardHiding {
+    fn from(val: HideGuard) -> Self {
+        match val {
+            HideGuard::Cond => guardgen_lib::doxygen::GuardHiding::Cond,
+            HideGuard::SkipThis => guardgen_lib::doxygen::GuardHiding::SkipThis,
+        }
+    }
+}
+
+/// Enum representing the minimum language standard of `--std`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Standard {
+    C99,
+    C11,
+    C17,
+    C23,
+    #[value(name = "c++11", alias = "cxx11")]
+    Cxx11,
+    #[value(name = "c++14", alias = "cxx14")]
+    Cxx14,
+    #[value(name = "c++17", alias = "cxx17")]
+    Cxx17,
+    #[value(name = "c++20", alias = "cxx20")]
+    Cxx20,
+    #[value(name = "c++23", alias = "cxx23")]
+    Cxx23,
+}
+
+impl From<Standard> for guardgen_lib::enforce::Standard {
+    fn from(val: Standard) -> Self {
+        use guardgen_lib::enforce::Standard as Lib;
+        match val {
+            Standard::C99 => Lib::C99,
+            Standard::C11 => Lib::C11,
+            Standard::C17 => Lib::C17,
+            Standard::C23 => Lib::C23,
+            Standard::Cxx11 => Lib::Cxx11,
+            Standard::Cxx14 => Lib::Cxx14,
+            Standard::Cxx17 => Lib::Cxx17,
+            Standard::Cxx20 => Lib::Cxx20,
+            Standard::Cxx23 => Lib::Cxx23,
+        }
+    }
+}
+
+/// Enum selecting how nested C++ namespaces are written.
+/// - `Classic`: One `namespace x {` per level.
+/// - `Nested`: C++17 `namespace a::b {`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum NamespaceStyle {
+    Classic,
+    Nested,
+}
+
+impl From<NamespaceStyle> for guardgen_lib::namespace::NamespaceStyle {
+    fn from(val: NamespaceStyle) -> Self {
+        match val {
+            NamespaceStyle::Classic => guardgen_lib::namespace::NamespaceStyle::Classic,
+            NamespaceStyle::Nested => guardgen_lib::namespace::NamespaceStyle::Nested,
+        }
+    }
+}
+
 /// Command-line argument parser using `clap`.
+///
+/// Without a subcommand the options of `new` are accepted directly, so the
+/// original `guardgen [OPTIONS]` invocation keeps working.
 #[derive(Parser, Debug)]
 #[command(
     author = "Daisuke Nagao",
@@ -76,16 +189,79 @@ impl From<UuidVersion> for guardgen_lib::UuidKind {
     long_about = "This tool generates unique include guards for C/C++ header files.\n\
                   The guard name is based on a UUID and optional prefix/suffix.\n\
                   It supports different languages and line-ending formats.\n\
-                  The output can be printed to stdout or written to a file."
+                  The output can be printed to stdout or written to a file.\n\
+                  Subcommands add, check, fix and inspect work on existing headers;\n\
+                  without a subcommand, guardgen behaves like `guardgen new`.",
+    args_conflicts_with_subcommands = true
 )]
-struct Args {
-    /// Output filename (if omitted, prints to stdout)
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Options of `new`, accepted without a subcommand
+    #[command(flatten)]
+    new: NewArgs,
+}
+
+impl Cli {
+    /// Parse `args`, keeping the output files of `new` in command-line order.
+    ///
+    /// Exits with clap's usage message on invalid arguments, like `Cli::parse`.
+    fn parse_ordered<I, T>(args: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        let matches = Self::command().get_matches_from(args);
+        let mut cli = Self::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        match (&mut cli.command, matches.subcommand()) {
+            (Some(Command::New(args)), Some((_, matches))) => args.order_outputs(matches),
+            (None, _) => cli.new.order_outputs(&matches),
+            _ => {}
+        }
+        cli
+    }
+}
+
+/// Subcommands of `guardgen`.
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generate a new include guard (default when no subcommand is given)
+    New(NewArgs),
+    /// Add an include guard to existing headers that have none
+    Add(ModifyArgs),
+    /// Report headers with missing or malformed include guards
+    Check(FilesArgs),
+    /// Repair the problems reported by `check` where possible
+    Fix(ModifyArgs),
+    /// Add the C linkage block to guarded headers that have none
+    Link(ModifyArgs),
+    /// Convert the line endings of existing headers to --line-ending
+    Convert(ModifyArgs),
+    /// Show the include guard of existing headers
+    Inspect(FilesArgs),
+    /// Restore the files rewritten by a previous run
+    Undo(UndoArgs),
+}
+
+/// Arguments of `new`.
+#[derive(Args, Debug)]
+struct NewArgs {
+    /// Output filenames (if omitted, prints to stdout)
     #[arg(
         short = 'o',
         long = "output",
-        help = "Specify the output file. If omitted, prints to stdout."
+        value_name = "FILE",
+        help = "Specify an output file; repeat to create several. If no file is given, prints to stdout."
+    )]
+    filenames: Vec<String>,
+
+    /// Further output files
+    #[arg(
+        value_name = "FILE",
+        help = "Output files to create, in addition to those given with -o; all are created in command-line order."
     )]
-    filename: Option<String>,
+    files: Vec<String>,
 
     /// Overwrite existing file if specified
     #[arg(
@@ -95,13 +271,468 @@ struct Args {
     )]
     overwrite: bool,
 
+    /// Create missing parent directories of the output files
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "Create missing parent directories of the output files."
+    )]
+    parents: bool,
+
+    /// Hide everything but the guard from the assembler
+    #[arg(
+        long = "assembler-safe",
+        default_value_t = false,
+        help = "Keep the guard visible to the assembler but place the linkage block and the body \
+                (or template body) inside #ifndef __ASSEMBLER__, for headers included from .S files."
+    )]
+    assembler_safe: bool,
+
+    /// Make a second inclusion an error
+    #[arg(
+        long = "strict",
+        default_value_t = false,
+        help = "Give the guard an #else branch with #error, so including the header twice \
+                in one translation unit fails to compile."
+    )]
+    strict: bool,
+
+    #[command(flatten)]
+    template: TemplateArgs,
+
+    #[command(flatten)]
+    spdx: SpdxArgs,
+
+    #[command(flatten)]
+    doxygen: DoxygenArgs,
+
+    #[command(flatten)]
+    namespace: NamespaceArgs,
+
+    #[command(flatten)]
+    enforce: EnforceArgs,
+
+    #[command(flatten)]
+    alias: AliasArgs,
+
+    #[command(flatten)]
+    guard: GuardArgs,
+
+    #[command(flatten)]
+    write: WriteArgs,
+}
+
+impl NewArgs {
+    /// Every output file: those given with `-o`, then the positional ones
+    /// (in command-line order once `order_outputs` ran).
+    fn outputs(&self) -> impl Iterator<Item = &str> {
+        self.filenames.iter().chain(&self.files).map(String::as_str)
+    }
+
+    /// Merge the `-o` and positional output files into `filenames` in the
+    /// order they appear on the command line.
+    fn order_outputs(&mut self, matches: &ArgMatches) {
+        let indexed = |id, files: &mut Vec<String>| {
+            let indices: Vec<usize> = matches.indices_of(id).into_iter().flatten().collect();
+            indices.into_iter().zip(std::mem::take(files))
+        };
+        let mut outputs: Vec<(usize, String)> = indexed("filenames", &mut self.filenames)
+            .chain(indexed("files", &mut self.files))
+            .collect();
+        outputs.sort_by_key(|(index, _)| *index);
+        self.filenames = outputs.into_iter().map(|(_, file)| file).collect();
+    }
+}
+
+/// Header skeleton options of `new`; each overrides the `[template]` table of the config.
+#[derive(Args, Debug)]
+struct TemplateArgs {
+    /// Template file for the header skeleton
+    #[arg(
+        long = "template",
+        value_name = "PATH",
+        help = "Create headers from the template at PATH. Placeholders: {{guard_open}}, {{guard_close}}, \
+                {{guard}}, {{file_name}}, {{date}}, {{year}}, {{author}}, {{project}}, \
+                {{namespace_open}}, {{namespace_close}}, {{namespace}}. \
+                A template without the guard placeholders is placed between the guard lines."
+    )]
+    template: Option<String>,
+
+    /// Value of the {{author}} placeholder
+    #[arg(
+        long = "author",
+        help = "Value of the {{author}} template placeholder."
+    )]
+    author: Option<String>,
+
+    /// Value of the {{project}} placeholder
+    #[arg(
+        long = "project",
+        help = "Value of the {{project}} template placeholder."
+    )]
+    project: Option<String>,
+}
+
+/// Doxygen options of `new`.
+#[derive(Args, Debug)]
+struct DoxygenArgs {
+    /// Emit a Doxygen file block
+    #[arg(
+        long = "doxygen",
+        default_value_t = false,
+        help = "Emit a /** @file ... */ Doxygen block above the guard."
+    )]
+    doxygen: bool,
+
+    /// Brief description for the file block
+    #[arg(
+        long = "brief",
+        value_name = "TEXT",
+        help = "Add an @brief line to the Doxygen file block (implies --doxygen)."
+    )]
+    brief: Option<String>,
+
+    /// Hide the guard macro from Doxygen
+    #[arg(
+        long = "hide-guard",
+        value_enum,
+        ignore_case = true,
+        help = "Hide the guard macro from generated documentation. \
+                Options: cond (wrap it in @cond/@endcond), skip-this (define it inside #ifndef DOXYGEN_SHOULD_SKIP_THIS)."
+    )]
+    hide_guard: Option<HideGuard>,
+}
+
+impl DoxygenArgs {
+    /// Whether to emit the file block.
+    fn file_block(&self) -> bool {
+        self.doxygen || self.brief.is_some()
+    }
+}
+
+/// Language and standard enforcement options of `new`.
+#[derive(Args, Debug)]
+struct EnforceArgs {
+    /// Reject compilers of the other language
+    #[arg(
+        long = "enforce-language",
+        default_value_t = false,
+        help = "Emit an #error after the guard's #define when the header is compiled as C++ \
+                although it is C (-x c, objc, opencl), as C although it is C++ (-x cxx, objcxx), \
+                or without a CUDA compiler (-x cuda)."
+    )]
+    language: bool,
+
+    /// Minimum standard
+    #[arg(
+        long = "std",
+        value_enum,
+        ignore_case = true,
+        help = "Emit an #error after the guard's #define when the compiler is older than the standard. \
+                Options: c99, c11, c17, c23 (checked in C compilations), \
+                c++11, c++14, c++17, c++20, c++23 (checked in C++ compilations)."
+    )]
+    standard: Option<Standard>,
+}
+
+impl EnforceArgs {
+    /// The enforcement for a header of `language`.
+    ///
+    /// A standard must belong to the header's language family: a C standard
+    /// for C, Objective-C and OpenCL, a C++ standard for the C++ dialects.
+    /// Without a language, either family is accepted.
+    fn resolve(
+        &self,
+        language: guardgen_lib::Language,
+    ) -> Result<guardgen_lib::enforce::Enforcement, String> {
+        use guardgen_lib::enforce::can_enforce_language;
+        if self.language && !can_enforce_language(language) {
+            return Err(
+                "Error: --enforce-language requires a C or C++ language (-x or the file extension)."
+                    .to_string(),
+            );
+        }
+        let standard: Option<guardgen_lib::enforce::Standard> = self.standard.map(Into::into);
+        if let Some(standard) = standard {
+            let matches = match language {
+                guardgen_lib::Language::None => true,
+                language if language.is_cxx() => standard.is_cxx(),
+                language => can_enforce_language(language) && !standard.is_cxx(),
+            };
+            if !matches {
+                return Err(format!(
+                    "Error: --std {} does not match the header's language (-x or the file extension).",
+                    standard
+                ));
+            }
+        }
+        Ok(guardgen_lib::enforce::Enforcement {
+            language: self.language,
+            standard,
+        })
+    }
+}
+
+/// Alias macro options of `new`.
+#[derive(Args, Debug)]
+struct AliasArgs {
+    /// Define a human-readable alias of the guard
+    #[arg(
+        long = "alias",
+        default_value_t = false,
+        help = "Also define a human-readable alias macro inside the guard, derived from the \
+                output path: include/acme/net/socket.h defines ACME_NET_SOCKET_H_INCLUDED."
+    )]
+    alias: bool,
+
+    /// Template of the alias
+    #[arg(
+        long = "alias-template",
+        value_name = "TEMPLATE",
+        help = "Build the alias from TEMPLATE (implies --alias). Placeholders: {{path}} (the path below \
+                include), {{file}} (the file name), {{stem}} (the file name without extension). \
+                Default: {{path}}_INCLUDED."
+    )]
+    alias_template: Option<String>,
+}
+
+impl AliasArgs {
+    /// The alias for the header at `path`, if one was requested.
+    fn resolve(&self, path: &str) -> Result<Option<String>, String> {
+        let template = match (&self.alias_template, self.alias) {
+            (Some(template), _) => template.as_str(),
+            (None, true) => guardgen_lib::alias::DEFAULT_TEMPLATE,
+            (None, false) => return Ok(None),
+        };
+        guardgen_lib::alias::alias_for(std::path::Path::new(path), template)
+            .map(Some)
+            .map_err(|e| format!("Error: Invalid alias for '{}': {}.", path, e))
+    }
+}
+
+/// C++ namespace options of `new`.
+#[derive(Args, Debug)]
+struct NamespaceArgs {
+    /// Namespace to open in the header
+    #[arg(
+        long = "namespace",
+        value_name = "NS",
+        help = "Open the C++ namespace NS (e.g. acme::net) inside the guard. Requires -x cxx."
+    )]
+    namespace: Option<String>,
+
+    /// Derive the namespace from the output path
+    #[arg(
+        long = "namespace-from-path",
+        default_value_t = false,
+        conflicts_with = "namespace",
+        help = "Derive the C++ namespace from the directories below 'include' in the output path, \
+                e.g. include/acme/net/socket.hpp opens acme::net. Requires -x cxx."
+    )]
+    from_path: bool,
+
+    /// Syntax of nested namespaces
+    #[arg(
+        long = "namespace-style",
+        value_enum,
+        ignore_case = true,
+        help = "How nested namespaces are written. Options: classic (one block per level, default), \
+                nested (C++17 namespace a::b)."
+    )]
+    style: Option<NamespaceStyle>,
+}
+
+impl NamespaceArgs {
+    /// The namespace of the header at `path` (empty when printing to stdout).
+    ///
+    /// @post Without namespace options, returns the global namespace.
+    fn resolve(
+        &self,
+        path: &str,
+        language: guardgen_lib::Language,
+    ) -> Result<guardgen_lib::namespace::Namespace, String> {
+        use guardgen_lib::namespace::Namespace;
+        if self.namespace.is_none() && !self.from_path {
+            return Ok(Namespace::default());
+        }
+        if !language.is_cxx() {
+            return Err(
+                "Error: C++ namespaces require -x cxx, objcxx or cuda, or a C++ header."
+                    .to_string(),
+            );
+        }
+        match &self.namespace {
+            Some(namespace) => Namespace::parse(namespace).map_err(|e| format!("Error: {}.", e)),
+            None if path.is_empty() => {
+                Err("Error: --namespace-from-path requires an output file.".to_string())
+            }
+            None => Ok(Namespace::from_path(std::path::Path::new(path))),
+        }
+    }
+
+    /// The namespace syntax, defaulting to classic.
+    fn style(&self) -> guardgen_lib::namespace::NamespaceStyle {
+        self.style.unwrap_or(NamespaceStyle::Classic).into()
+    }
+}
+
+/// SPDX line options; each overrides the `[spdx]` table of the config.
+///
+/// The lines are emitted once a license or copyright holder is set. The holder
+/// then defaults to `git config user.name`, the year to the current one.
+#[derive(Args, Debug)]
+struct SpdxArgs {
+    /// SPDX license expression
+    #[arg(
+        long = "spdx-license",
+        value_name = "ID",
+        help = "Emit an SPDX-License-Identifier line with this license expression, e.g. MIT."
+    )]
+    license: Option<String>,
+
+    /// Copyright holder
+    #[arg(
+        long = "spdx-copyright",
+        value_name = "HOLDER",
+        help = "Emit an SPDX-FileCopyrightText line for HOLDER. Default: git config user.name."
+    )]
+    holder: Option<String>,
+
+    /// Copyright year
+    #[arg(
+        long = "spdx-year",
+        value_name = "YEAR",
+        help = "Year (or range) in the SPDX-FileCopyrightText line. Default: the current year."
+    )]
+    year: Option<String>,
+}
+
+impl SpdxArgs {
+    /// The SPDX lines to emit, or `None` when neither a license nor a holder is set.
+    fn resolve(
+        &self,
+        config: config::SpdxConfig,
+        guard: &GuardArgs,
+    ) -> Result<Option<guardgen_lib::spdx::SpdxHeader>, String> {
+        let license = self.license.clone().or(config.license);
+        let holder = self.holder.clone().or(config.holder);
+        if license.is_none() && holder.is_none() {
+            return Ok(None);
+        }
+        let holder = holder.or_else(git_user_name);
+        let year = match self.year.clone().or(config.year) {
+            Some(year) => year,
+            None => guard.today()?.0.to_string(),
+        };
+        Ok(Some(guardgen_lib::spdx::SpdxHeader {
+            license,
+            copyright: holder.map(|holder| format!("{} {}", year, holder)),
+        }))
+    }
+}
+
+/// `git config user.name`, if git is available and it is set.
+fn git_user_name() -> Option<String> {
+    let output = std::process::Command::new("git")
+        .args(["config", "user.name"])
+        .output()
+        .ok()?;
+    let name = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (output.status.success() && !name.is_empty()).then_some(name)
+}
+
+/// Arguments of the subcommands working on existing headers.
+#[derive(Args, Debug)]
+struct FilesArgs {
+    /// Header files to process
+    #[arg(required = true, value_name = "FILE")]
+    files: Vec<String>,
+
+    #[command(flatten)]
+    guard: GuardArgs,
+}
+
+/// Arguments of the subcommands rewriting existing headers.
+#[derive(Args, Debug)]
+struct ModifyArgs {
+    /// Header files to process
+    #[arg(required = true, value_name = "FILE")]
+    files: Vec<String>,
+
+    #[command(flatten)]
+    spdx: SpdxArgs,
+
+    #[command(flatten)]
+    guard: GuardArgs,
+
+    #[command(flatten)]
+    write: WriteArgs,
+}
+
+/// Arguments of `undo`.
+#[derive(Args, Debug)]
+struct UndoArgs {
+    /// Run to undo (default: the most recent one not undone yet)
+    #[arg(
+        value_name = "RUN_ID",
+        help = "The run to undo, as printed when it wrote its files. Default: the most recent run not undone yet."
+    )]
+    run_id: Option<String>,
+
+    /// List the journaled runs
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with = "run_id",
+        help = "List the runs recorded in the backup journal instead of undoing one."
+    )]
+    list: bool,
+}
+
+/// Options of the subcommands that write files.
+#[derive(Args, Debug)]
+struct WriteArgs {
+    /// Print a unified diff instead of writing
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "Print a unified diff of the intended changes instead of writing any file."
+    )]
+    dry_run: bool,
+
+    /// Exit non-zero if anything would change
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with = "dry_run",
+        help = "Write nothing; exit with status 1 if any file would be changed."
+    )]
+    check: bool,
+}
+
+impl WriteArgs {
+    /// The selected write mode.
+    fn mode(&self) -> commands::WriteMode {
+        match (self.dry_run, self.check) {
+            (true, _) => commands::WriteMode::DryRun,
+            (_, true) => commands::WriteMode::Check,
+            _ => commands::WriteMode::Write,
+        }
+    }
+}
+
+/// Options shared by every subcommand.
+///
+/// Generating subcommands fall back to the documented defaults; `check` only
+/// verifies the options that were given explicitly.
+#[derive(Args, Debug)]
+struct GuardArgs {
     /// Prefix for the include guard (default: "UUID")
     #[arg(
         long = "prefix",
-        default_value = "UUID",
         help = "Specify a prefix for the include guard. Default: 'UUID'."
     )]
-    prefix: String,
+    prefix: Option<String>,
 
     /// Suffix for the include guard (optional)
     #[arg(long = "suffix", default_value = None, help = "Specify an optional suffix for the include guard.")]
@@ -111,97 +742,304 @@ struct Args {
     #[arg(
         short,
         value_enum,
-        default_value_t = Language::None,
         ignore_case = true,
         help = "Specify the language for compatibility adjustments. \
-                Options: none (default), c (adds extern \"C\" blocks), cxx (no additional modification)."
+                Options: none, c (adds extern \"C\" blocks), cxx (no additional modification), \
+                objc, objcxx (import Foundation and assume nonnull), cuda (C++ without a linkage block), \
+                opencl, glsl, hlsl. \
+                Default: inferred from the file extension (.h: c; .hh, .hpp, .hxx, .inl: cxx; .cuh: cuda; \
+                .clh: opencl; .glsl: glsl; .hlsli, .fxh: hlsl; \
+                add and fix treat a .h file using C++-only constructs as cxx), otherwise none."
     )]
-    x: Language,
+    x: Option<Language>,
 
     /// Line-ending style (LF/CRLF)
     #[arg(
         long = "line-ending",
         value_enum,
-        default_value_t = LineEnding::None,
         ignore_case = true,
         help = "Specify the line-ending style. \
                 Options: none (auto-detect), lf (Unix-style LF), crlf (Windows-style CRLF)."
     )]
-    line_ending: LineEnding,
+    line_ending: Option<LineEnding>,
 
     /// UUID version used for the include guard
     #[arg(
         short = 'v',
         long = "uuid-version",
         value_enum,
-        default_value_t = UuidVersion::V7,
         ignore_case = true,
         help = "Specify the UUID version for the include guard. \
                 Options: v7 (default), v4. Compact forms -v7 and -v4 are accepted."
     )]
-    uuid_version: UuidVersion,
+    uuid_version: Option<UuidVersion>,
+
+    /// Ordering of UUID v7 guards within one millisecond
+    #[arg(
+        long = "v7-ordering",
+        value_enum,
+        default_value_t = V7Ordering::Counter,
+        ignore_case = true,
+        help = "Specify how UUID v7 guards generated within one millisecond are ordered. \
+                Options: counter (default), sub-millisecond (RFC 9562 method 3 clock fraction)."
+    )]
+    v7_ordering: V7Ordering,
+
+    /// Fixed Unix timestamp for UUID v7 generation
+    #[arg(
+        long = "timestamp",
+        value_name = "SECONDS",
+        help = "Use a fixed Unix timestamp (seconds) for UUID v7 generation. \
+                Defaults to SOURCE_DATE_EPOCH when that variable is set. At most 281474976710."
+    )]
+    timestamp: Option<u64>,
+
+    /// Seed for the random bits of the UUID
+    #[arg(
+        long = "seed",
+        help = "Seed the random bits of the UUID so the same inputs give identical output."
+    )]
+    seed: Option<u64>,
+
+    /// State file ordering UUID v7 guards across invocations
+    #[arg(
+        long = "state-file",
+        value_name = "PATH",
+        help = "Record the last-issued UUID v7 timestamp and counter in PATH (locked while in use) \
+                so successive invocations on this machine produce strictly increasing guards. \
+                Ignored with --dry-run and --check."
+    )]
+    state_file: Option<String>,
+
+    /// Macro opening the C linkage block
+    #[arg(
+        long = "linkage-begin",
+        value_name = "MACRO",
+        help = "With -x c, open the linkage block with MACRO (e.g. __BEGIN_DECLS) instead of \
+                #ifdef __cplusplus / extern \"C\" {. Requires --linkage-end."
+    )]
+    linkage_begin: Option<String>,
+
+    /// Macro closing the C linkage block
+    #[arg(
+        long = "linkage-end",
+        value_name = "MACRO",
+        help = "With -x c, close the linkage block with MACRO (e.g. __END_DECLS). Requires --linkage-begin."
+    )]
+    linkage_end: Option<String>,
+
+    /// Audit C linkage blocks in check and fix
+    #[arg(
+        long = "audit-linkage",
+        default_value_t = false,
+        help = "In check and fix, also report functions declared outside an extern \"C\" block, \
+                unbalanced linkage blocks and #include lines inside them."
+    )]
+    audit_linkage: bool,
+
+    /// Configuration file
+    #[arg(
+        long = "config",
+        value_name = "PATH",
+        help = "Read the configuration from PATH instead of the nearest guardgen.toml."
+    )]
+    config: Option<String>,
 }
 
-/// Main function that parses arguments and generates the include guard.
-fn main() {
-    // Parse command-line arguments using `clap`.
-    let args = Args::parse();
-
-    // Generate the include guard based on user input using the struct-based API.
-    let mut generator = guardgen_lib::IncludeGuardGenerator::new();
-    let guard = generator.generate(
-        args.prefix,
-        args.suffix,
-        args.x.into(),
-        args.line_ending.into(),
-        args.uuid_version.into(),
-    );
-
-    if let Some(file_path) = &args.filename {
-        // Check if the file already exists and prevent overwriting unless explicitly allowed.
-        if !args.overwrite && fs::metadata(file_path).is_ok() {
-            eprintln!(
-                "Error: File '{}' already exists. Use --overwrite to overwrite.",
-                file_path
-            );
-            std::process::exit(1);
-        }
-
-        // Attempt to open the file for writing.
-        match OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(args.overwrite)
-            .open(file_path)
-        {
-            Ok(mut file) => {
-                // Write the generated include guard to the file.
-                if let Err(e) = file.write_all(guard.as_bytes()) {
-                    eprintln!("Error writing to file '{}': {}", file_path, e);
-                    std::process::exit(1);
-                }
-                println!("Guard written to '{}'.", file_path);
-            }
-            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
-                eprintln!(
-                    "Error: File '{}' already exists. Use --overwrite to overwrite.",
-                    file_path
-                );
-                std::process::exit(1);
-            }
-            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
-                eprintln!("Error: Permission denied when accessing '{}'.", file_path);
-                std::process::exit(1);
+impl GuardArgs {
+    /// Guard prefix, defaulting to "UUID".
+    fn prefix(&self) -> &str {
+        self.prefix.as_deref().unwrap_or("UUID")
+    }
+
+    /// Target language of the header at `path`: `-x`, else the language of its
+    /// extension (the `[languages]` table first, then the defaults), else `none`.
+    ///
+    /// `text` is the content of an existing header. A `.h` header the table does
+    /// not map is C++ when it uses constructs only C++ accepts.
+    fn language_for(
+        &self,
+        path: &str,
+        text: Option<&str>,
+        languages: &HashMap<String, Language>,
+    ) -> guardgen_lib::Language {
+        use guardgen_lib::detect;
+        if let Some(language) = self.x {
+            return language.into();
+        }
+        let path = std::path::Path::new(path);
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        if let Some(&language) = languages.get(extension) {
+            return language.into();
+        }
+        match text {
+            Some(text)
+                if extension == detect::AMBIGUOUS_EXTENSION && detect::looks_like_cxx(text) =>
+            {
+                guardgen_lib::Language::Cxx
             }
-            Err(e) => {
-                eprintln!("Error creating file '{}': {}", file_path, e);
-                std::process::exit(1);
+            _ => detect::language_for_path(path).unwrap_or(guardgen_lib::Language::None),
+        }
+    }
+
+    /// Line-ending style, defaulting to the system default.
+    fn line_ending(&self) -> guardgen_lib::LineEnding {
+        self.line_ending.unwrap_or(LineEnding::None).into()
+    }
+
+    /// UUID version, defaulting to v7.
+    fn uuid_kind(&self) -> guardgen_lib::UuidKind {
+        self.uuid_version.unwrap_or(UuidVersion::V7).into()
+    }
+
+    /// Expectations for `check`, made only of the options given explicitly.
+    fn check_options(
+        &self,
+        linkage: guardgen_lib::linkage::Linkage,
+    ) -> guardgen_lib::header::CheckOptions {
+        guardgen_lib::header::CheckOptions {
+            prefix: self.prefix.clone(),
+            suffix: self.suffix.clone(),
+            uuid_kind: self.uuid_version.map(Into::into),
+            language: self.x.map(Into::into),
+            line_ending: self.line_ending.map(Into::into),
+            linkage,
+            audit_linkage: self.audit_linkage,
+        }
+    }
+
+    /// The C linkage block, from `--linkage-begin`/`--linkage-end` or the `[linkage]` table.
+    ///
+    /// @post Without either macro, returns the `extern "C"` block.
+    fn linkage(
+        &self,
+        config: config::LinkageConfig,
+    ) -> Result<guardgen_lib::linkage::Linkage, String> {
+        use guardgen_lib::linkage::Linkage;
+        let begin = self.linkage_begin.clone().or(config.begin);
+        let end = self.linkage_end.clone().or(config.end);
+        match (begin, end) {
+            (None, None) => Ok(Linkage::ExternC),
+            (Some(begin), Some(end)) => {
+                for name in [&begin, &end] {
+                    let valid = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                    if !valid {
+                        return Err(format!("Error: '{}' is not a valid macro name.", name));
+                    }
+                }
+                Ok(Linkage::macros(&begin, &end))
             }
+            _ => Err("Error: The linkage begin and end macros must be set together.".to_string()),
         }
-    } else {
-        // Print the include guard to stdout if no output file is specified.
-        println!("{}", guard);
     }
+
+    /// The project configuration selected by `--config`.
+    fn config(&self) -> Result<config::Config, String> {
+        config::Config::load(self.config.as_deref())
+    }
+
+    /// The fixed timestamp from `--timestamp` or `SOURCE_DATE_EPOCH`, if any.
+    fn fixed_timestamp(&self) -> Result<Option<u64>, String> {
+        resolve_timestamp(self.timestamp, std::env::var("SOURCE_DATE_EPOCH").ok())
+    }
+
+    /// Today's UTC `(year, month, day)`, honouring `--timestamp` and `SOURCE_DATE_EPOCH`.
+    fn today(&self) -> Result<(i64, i64, i64), String> {
+        let seconds = match self.fixed_timestamp()? {
+            Some(seconds) => seconds,
+            None => std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+        };
+        let millis = seconds
+            .checked_mul(1000)
+            .ok_or_else(|| format!("Error: Timestamp {} is out of range.", seconds))?;
+        Ok(commands::civil_date(millis))
+    }
+
+    /// Build the generator configured by the ordering, reproducibility and state options.
+    ///
+    /// A run in `mode` that only previews its changes neither creates nor
+    /// advances the state file, since the guards it shows are never written.
+    fn generator(
+        &self,
+        mode: commands::WriteMode,
+    ) -> Result<guardgen_lib::IncludeGuardGenerator, String> {
+        let timestamp = self.fixed_timestamp()?;
+
+        let mut generator = guardgen_lib::IncludeGuardGenerator::new();
+        generator.set_v7_ordering(self.v7_ordering.into());
+        if let Some(seconds) = timestamp {
+            generator.set_timestamp(seconds);
+        }
+        if let Some(seed) = self.seed {
+            generator.set_seed(seed);
+        }
+        if let (Some(state_path), commands::WriteMode::Write) = (&self.state_file, mode) {
+            let state_file = guardgen_lib::V7StateFile::open(state_path)
+                .map_err(|e| format!("Error opening state file '{}': {}", state_path, e))?;
+            generator.set_state_file(state_file);
+        }
+        Ok(generator)
+    }
+
+    /// Describe a failure of `try_generate`/`try_next_guard` for the user.
+    fn generation_error(&self, e: std::io::Error) -> String {
+        format!(
+            "Error updating state file '{}': {}",
+            self.state_file.as_deref().unwrap_or_default(),
+            e
+        )
+    }
+}
+
+/// Latest timestamp, in seconds, whose milliseconds fit the 48-bit v7 timestamp field.
+const MAX_TIMESTAMP: u64 = ((1 << 48) - 1) / 1000;
+
+/// Resolve the timestamp to use: `--timestamp` wins over `SOURCE_DATE_EPOCH`.
+///
+/// @post `Ok(Some(seconds))` has `seconds <= MAX_TIMESTAMP`.
+fn resolve_timestamp(
+    timestamp: Option<u64>,
+    source_date_epoch: Option<String>,
+) -> Result<Option<u64>, String> {
+    let (seconds, source) = match (timestamp, source_date_epoch) {
+        (Some(seconds), _) => (seconds, "--timestamp"),
+        (None, Some(value)) if !value.is_empty() => {
+            let seconds = value
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("Error: Invalid SOURCE_DATE_EPOCH value '{}'.", value))?;
+            (seconds, "SOURCE_DATE_EPOCH")
+        }
+        (None, _) => return Ok(None),
+    };
+    if seconds > MAX_TIMESTAMP {
+        return Err(format!(
+            "Error: {} value {} is past the largest UUID v7 timestamp ({}).",
+            source, seconds, MAX_TIMESTAMP
+        ));
+    }
+    Ok(Some(seconds))
+}
+
+/// Main function that parses arguments and dispatches to the selected subcommand.
+fn main() {
+    // Parse command-line arguments using `clap`.
+    let cli = Cli::parse_ordered(std::env::args_os());
+
+    let code = match cli.command.unwrap_or(Command::New(cli.new)) {
+        Command::New(args) => commands::new::run(&args),
+        Command::Add(args) => commands::add::run(&args),
+        Command::Check(args) => commands::check::run(&args),
+        Command::Fix(args) => commands::fix::run(&args),
+        Command::Link(args) => commands::link::run(&args),
+        Command::Convert(args) => commands::convert::run(&args),
+        Command::Inspect(args) => commands::inspect::run(&args),
+        Command::Undo(args) => commands::undo::run(&args),
+    };
+    std::process::exit(code);
 }
 
 #[cfg(test)]
@@ -211,24 +1049,303 @@ mod tests {
 
     #[test]
     fn default_uuid_version_is_v7() {
-        let args = Args::parse_from(["guardgen"]);
+        let cli = Cli::parse_from(["guardgen"]);
 
-        assert_eq!(args.uuid_version, UuidVersion::V7);
+        assert_eq!(cli.new.guard.uuid_kind(), guardgen_lib::UuidKind::V7);
     }
 
     #[test]
     fn parses_compact_uuid_version_flags() {
-        let args_v4 = Args::parse_from(["guardgen", "-v4"]);
-        let args_v7 = Args::parse_from(["guardgen", "-v7"]);
+        let args_v4 = Cli::parse_from(["guardgen", "-v4"]);
+        let args_v7 = Cli::parse_from(["guardgen", "-v7"]);
 
-        assert_eq!(args_v4.uuid_version, UuidVersion::V4);
-        assert_eq!(args_v7.uuid_version, UuidVersion::V7);
+        assert_eq!(args_v4.new.guard.uuid_version, Some(UuidVersion::V4));
+        assert_eq!(args_v7.new.guard.uuid_version, Some(UuidVersion::V7));
     }
 
     #[test]
     fn rejects_repeated_uuid_version_selection() {
-        let err = Args::try_parse_from(["guardgen", "-v4", "-v7"]).unwrap_err();
+        let err = Cli::try_parse_from(["guardgen", "-v4", "-v7"]).unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn timestamp_option_overrides_source_date_epoch() {
+        assert_eq!(resolve_timestamp(None, None), Ok(None));
+        assert_eq!(
+            resolve_timestamp(None, Some("1700000000".to_string())),
+            Ok(Some(1_700_000_000))
+        );
+        assert_eq!(
+            resolve_timestamp(Some(5), Some("1700000000".to_string())),
+            Ok(Some(5))
+        );
+        assert!(resolve_timestamp(None, Some("yesterday".to_string())).is_err());
+
+        // The milliseconds must fit the 48-bit v7 timestamp.
+        assert_eq!(MAX_TIMESTAMP, 281_474_976_710);
+        assert_eq!(
+            resolve_timestamp(Some(MAX_TIMESTAMP), None),
+            Ok(Some(MAX_TIMESTAMP))
+        );
+        assert!(resolve_timestamp(Some(MAX_TIMESTAMP + 1), None).is_err());
+        assert!(resolve_timestamp(None, Some("281474976711".to_string())).is_err());
+    }
+
+    #[test]
+    fn today_follows_the_timestamp_past_the_year_9999() {
+        let today = |timestamp: &str| {
+            Cli::parse_from(["guardgen", "--timestamp", timestamp])
+                .new
+                .guard
+                .today()
+        };
+
+        assert_eq!(today("0"), Ok((1970, 1, 1)));
+        assert_eq!(today("1700000000"), Ok((2023, 11, 14)));
+        assert_eq!(today("281474976710"), Ok((10889, 8, 2)));
+        assert!(today("18446744073709551615").is_err());
+    }
+
+    #[test]
+    fn parses_v7_ordering() {
+        let default = Cli::parse_from(["guardgen"]);
+        let precise = Cli::parse_from(["guardgen", "--v7-ordering", "precision"]);
+
+        assert_eq!(default.new.guard.v7_ordering, V7Ordering::Counter);
+        assert_eq!(precise.new.guard.v7_ordering, V7Ordering::SubMillisecond);
+    }
+
+    #[test]
+    fn bare_invocation_is_an_alias_for_new() {
+        let bare = Cli::parse_from(["guardgen", "--prefix", "ACME", "-o", "a.h"]);
+        assert!(bare.command.is_none());
+        assert_eq!(bare.new.guard.prefix(), "ACME");
+        assert_eq!(bare.new.outputs().collect::<Vec<_>>(), ["a.h"]);
+
+        let explicit = Cli::parse_from(["guardgen", "new", "--prefix", "ACME", "-o", "a.h"]);
+        let Some(Command::New(args)) = explicit.command else {
+            panic!("expected the new subcommand");
+        };
+        assert_eq!(args.guard.prefix(), "ACME");
+        assert_eq!(args.outputs().collect::<Vec<_>>(), ["a.h"]);
+    }
+
+    #[test]
+    fn collects_many_output_files() {
+        let cli = Cli::parse_ordered([
+            "guardgen",
+            "new",
+            "a.h",
+            "-o",
+            "b.h",
+            "sub/c.h",
+            "-o",
+            "d.h",
+            "--parents",
+        ]);
+        let Some(Command::New(args)) = cli.command else {
+            panic!("expected the new subcommand");
+        };
+
+        assert_eq!(
+            args.outputs().collect::<Vec<_>>(),
+            ["a.h", "b.h", "sub/c.h", "d.h"]
+        );
+        assert!(args.parents);
+
+        let bare = Cli::parse_ordered(["guardgen", "a.h", "-o", "b.h", "c.h"]);
+        assert!(bare.command.is_none());
+        assert_eq!(
+            bare.new.outputs().collect::<Vec<_>>(),
+            ["a.h", "b.h", "c.h"]
+        );
+    }
+
+    #[test]
+    fn shared_options_apply_to_file_subcommands() {
+        let cli = Cli::parse_from(["guardgen", "check", "-x", "c", "--prefix", "ACME", "a.h"]);
+        let Some(Command::Check(args)) = cli.command else {
+            panic!("expected the check subcommand");
+        };
+        let options = args
+            .guard
+            .check_options(guardgen_lib::linkage::Linkage::ExternC);
+
+        assert_eq!(args.files, vec!["a.h".to_string()]);
+        assert_eq!(options.prefix.as_deref(), Some("ACME"));
+        assert_eq!(options.language, Some(guardgen_lib::Language::C));
+        assert_eq!(options.uuid_kind, None);
+
+        assert!(Cli::try_parse_from(["guardgen", "fix"]).is_err());
+    }
+
+    #[test]
+    fn resolves_namespaces_for_cxx_only() {
+        let cli = Cli::parse_from(["guardgen", "-x", "cxx", "--namespace-from-path"]);
+        let namespace = cli
+            .new
+            .namespace
+            .resolve("include/acme/net/socket.hpp", guardgen_lib::Language::Cxx)
+            .unwrap();
+        assert_eq!(namespace.to_string(), "acme::net");
+
+        let c = Cli::parse_from(["guardgen", "-x", "c", "--namespace", "acme"]);
+        assert!(
+            c.new
+                .namespace
+                .resolve("a.h", guardgen_lib::Language::C)
+                .is_err()
+        );
+        assert!(
+            Cli::try_parse_from(["guardgen", "--namespace", "a", "--namespace-from-path"]).is_err()
+        );
+    }
+
+    #[test]
+    fn resolves_enforcement_for_the_language() {
+        use guardgen_lib::enforce::{Enforcement, Standard as Lib};
+        let cli = Cli::parse_from(["guardgen", "--enforce-language", "--std", "C++17"]);
+        assert_eq!(
+            cli.new.enforce.resolve(guardgen_lib::Language::Cxx),
+            Ok(Enforcement {
+                language: true,
+                standard: Some(Lib::Cxx17),
+            })
+        );
+        assert!(
+            cli.new
+                .enforce
+                .resolve(guardgen_lib::Language::None)
+                .is_err()
+        );
+        let alias = Cli::parse_from(["guardgen", "--std", "cxx20"]);
+        assert_eq!(alias.new.enforce.standard, Some(Standard::Cxx20));
+
+        let cxx = Cli::parse_from(["guardgen", "--std", "c++17"]);
+        let c = Cli::parse_from(["guardgen", "--std", "c11"]);
+        for language in [guardgen_lib::Language::C, guardgen_lib::Language::OpenCl] {
+            assert!(cxx.new.enforce.resolve(language).is_err());
+            assert!(c.new.enforce.resolve(language).is_ok());
+        }
+        for language in [guardgen_lib::Language::Cxx, guardgen_lib::Language::Cuda] {
+            assert!(c.new.enforce.resolve(language).is_err());
+            assert!(cxx.new.enforce.resolve(language).is_ok());
+        }
+        assert!(c.new.enforce.resolve(guardgen_lib::Language::Glsl).is_err());
+        assert!(c.new.enforce.resolve(guardgen_lib::Language::None).is_ok());
+    }
+
+    #[test]
+    fn resolves_aliases_from_the_output_path() {
+        let cli = Cli::parse_from(["guardgen", "--alias"]);
+        assert_eq!(
+            cli.new.alias.resolve("include/acme/net/socket.h"),
+            Ok(Some("ACME_NET_SOCKET_H_INCLUDED".to_string()))
+        );
+        assert!(cli.new.alias.resolve("").is_err());
 
+        let templated = Cli::parse_from(["guardgen", "--alias-template", "ACME_{{stem}}_H"]);
+        assert_eq!(
+            templated.new.alias.resolve("src/socket.h"),
+            Ok(Some("ACME_SOCKET_H".to_string()))
+        );
+        let none = Cli::parse_from(["guardgen"]);
+        assert_eq!(none.new.alias.resolve("a.h"), Ok(None));
+    }
+
+    #[test]
+    fn combines_linkage_macros_with_the_config() {
+        use guardgen_lib::linkage::Linkage;
+        let cli = Cli::parse_from(["guardgen", "-x", "c", "--linkage-end", "ACME_EXTERN_C_END"]);
+        let config = config::LinkageConfig {
+            begin: Some("ACME_EXTERN_C_BEGIN".to_string()),
+            end: Some("ACME_END".to_string()),
+        };
+
+        assert_eq!(
+            cli.new.guard.linkage(config),
+            Ok(Linkage::macros("ACME_EXTERN_C_BEGIN", "ACME_EXTERN_C_END"))
+        );
+        assert!(cli.new.guard.linkage(Default::default()).is_err());
+        assert_eq!(
+            Cli::parse_from(["guardgen"])
+                .new
+                .guard
+                .linkage(Default::default()),
+            Ok(Linkage::ExternC)
+        );
+    }
+
+    #[test]
+    fn infers_language_from_file_names() {
+        use guardgen_lib::Language as Lib;
+        let guard = Cli::parse_from(["guardgen"]).new.guard;
+        let mut languages = HashMap::new();
+
+        assert_eq!(guard.language_for("a.hpp", None, &languages), Lib::Cxx);
+        assert_eq!(guard.language_for("a.h", None, &languages), Lib::C);
+        assert_eq!(
+            guard.language_for("a.h", Some("class A;\n"), &languages),
+            Lib::Cxx
+        );
+        assert_eq!(guard.language_for("", None, &languages), Lib::None);
+
+        languages.insert("h".to_string(), Language::Cxx);
+        assert_eq!(guard.language_for("a.h", None, &languages), Lib::Cxx);
+        let explicit = Cli::parse_from(["guardgen", "-x", "none"]).new.guard;
+        assert_eq!(explicit.language_for("a.h", None, &languages), Lib::None);
+
+        assert_eq!(guard.language_for("k.cuh", None, &languages), Lib::Cuda);
+        assert_eq!(guard.language_for("s.glsl", None, &languages), Lib::Glsl);
+        let objcxx = Cli::parse_from(["guardgen", "-x", "objc++"]).new.guard;
+        assert_eq!(objcxx.language_for("a.h", None, &languages), Lib::ObjCxx);
+    }
+
+    #[test]
+    fn parses_write_modes() {
+        let cli = Cli::parse_from(["guardgen", "fix", "--dry-run", "a.h"]);
+        let Some(Command::Fix(args)) = cli.command else {
+            panic!("expected the fix subcommand");
+        };
+        assert_eq!(args.write.mode(), commands::WriteMode::DryRun);
+
+        let bare = Cli::parse_from(["guardgen", "--check", "a.h"]);
+        assert_eq!(bare.new.write.mode(), commands::WriteMode::Check);
+
+        let err =
+            Cli::try_parse_from(["guardgen", "add", "--dry-run", "--check", "a.h"]).unwrap_err();
         assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
     }
+
+    #[test]
+    fn previews_leave_the_state_file_alone() {
+        let path =
+            std::env::temp_dir().join(format!("guardgen-preview-state-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let cli = Cli::parse_from([
+            "guardgen",
+            "--dry-run",
+            "--state-file",
+            path.to_str().unwrap(),
+        ]);
+
+        for mode in [commands::WriteMode::DryRun, commands::WriteMode::Check] {
+            let mut generator = cli.new.guard.generator(mode).unwrap();
+            generator
+                .try_next_guard(
+                    "UUID",
+                    None,
+                    guardgen_lib::Language::None,
+                    guardgen_lib::LineEnding::LF,
+                    guardgen_lib::UuidKind::V7,
+                )
+                .unwrap();
+            assert!(!path.exists());
+        }
+        cli.new.guard.generator(commands::WriteMode::Write).unwrap();
+        assert!(path.exists());
+        std::fs::remove_file(&path).unwrap();
+    }
 }
@@ -0,0 +1,338 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! C++ namespace blocks for generated headers.
+
+use std::fmt;
+use std::path::{Component, Path};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen::prelude::*;
+
+/// Directory below which header paths mirror namespaces.
+pub const INCLUDE_DIR: &str = "include";
+
+/// C++ keywords and alternative operator tokens, which cannot name a namespace.
+const KEYWORDS: &[&str] = &[
+    "alignas",
+    "alignof",
+    "and",
+    "and_eq",
+    "asm",
+    "auto",
+    "bitand",
+    "bitor",
+    "bool",
+    "break",
+    "case",
+    "catch",
+    "char",
+    "char8_t",
+    "char16_t",
+    "char32_t",
+    "class",
+    "co_await",
+    "co_return",
+    "co_yield",
+    "compl",
+    "concept",
+    "const",
+    "const_cast",
+    "consteval",
+    "constexpr",
+    "constinit",
+    "continue",
+    "decltype",
+    "default",
+    "delete",
+    "do",
+    "double",
+    "dynamic_cast",
+    "else",
+    "enum",
+    "explicit",
+    "export",
+    "extern",
+    "false",
+    "float",
+    "for",
+    "friend",
+    "goto",
+    "if",
+    "inline",
+    "int",
+    "long",
+    "mutable",
+    "namespace",
+    "new",
+    "noexcept",
+    "not",
+    "not_eq",
+    "nullptr",
+    "operator",
+    "or",
+    "or_eq",
+    "private",
+    "protected",
+    "public",
+    "register",
+    "reinterpret_cast",
+    "requires",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "static_assert",
+    "static_cast",
+    "struct",
+    "switch",
+    "template",
+    "this",
+    "thread_local",
+    "throw",
+    "true",
+    "try",
+    "typedef",
+    "typeid",
+    "typename",
+    "union",
+    "unsigned",
+    "using",
+    "virtual",
+    "void",
+    "volatile",
+    "wchar_t",
+    "while",
+    "xor",
+    "xor_eq",
+];
+
+/// Syntax used to open and close nested namespaces.
+/// - `Classic`: One `namespace x {` per level; valid in every C++ standard.
+/// - `Nested`: C++17 `namespace a::b {`.
+#[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum NamespaceStyle {
+    #[default]
+    Classic,
+    Nested,
+}
+
+/// Error for a namespace that is not a `::`-separated list of identifiers
+/// other than C++ keywords.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidNamespace(pub String);
+
+impl fmt::Display for InvalidNamespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}' is not a valid C++ namespace", self.0)
+    }
+}
+
+impl std::error::Error for InvalidNamespace {}
+
+/// A possibly nested C++ namespace such as `acme::net`.
+///
+/// @invariant Every component is a non-empty C++ identifier and not a keyword.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Namespace {
+    components: Vec<String>,
+}
+
+impl Namespace {
+    /// Parse `a::b::c`. An empty string gives the global namespace.
+    pub fn parse(text: &str) -> Result<Self, InvalidNamespace> {
+        let text = text.trim().trim_start_matches("::");
+        if text.is_empty() {
+            return Ok(Namespace::default());
+        }
+        let components: Vec<String> = text.split("::").map(|c| c.trim().to_string()).collect();
+        if components
+            .iter()
+            .all(|c| is_identifier(c) && !is_keyword(c))
+        {
+            Ok(Namespace { components })
+        } else {
+            Err(InvalidNamespace(text.to_string()))
+        }
+    }
+
+    /// Derive the namespace from the directories of a header path.
+    ///
+    /// The directories below the last `include` directory are used, or all
+    /// directories of a relative path without one; `include/acme/net/socket.hpp`
+    /// gives `acme::net`. Characters invalid in identifiers become `_`, and a
+    /// directory named like a C++ keyword gets a trailing `_` (`new` gives `new_`).
+    pub fn from_path(path: &Path) -> Self {
+        let dirs: Vec<&str> = path
+            .parent()
+            .into_iter()
+            .flat_map(Path::components)
+            .filter_map(|component| match component {
+                Component::Normal(name) => name.to_str(),
+                _ => None,
+            })
+            .collect();
+        let below_include = match dirs.iter().rposition(|dir| *dir == INCLUDE_DIR) {
+            Some(i) => &dirs[i + 1..],
+            None if path.is_absolute() => &[],
+            None => &dirs[..],
+        };
+        Namespace {
+            components: below_include
+                .iter()
+                .map(|dir| {
+                    let mut ident = sanitize(dir);
+                    if is_keyword(&ident) {
+                        ident.push('_');
+                    }
+                    ident
+                })
+                .collect(),
+        }
+    }
+
+    /// Whether this is the global namespace, i.e. nothing is emitted.
+    pub fn is_global(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// The lines opening the namespace, each ending with `newline`.
+    pub fn open(&self, style: NamespaceStyle, newline: &str) -> String {
+        match style {
+            _ if self.is_global() => String::new(),
+            NamespaceStyle::Nested => format!("namespace {} {{{}", self, newline),
+            NamespaceStyle::Classic => self
+                .components
+                .iter()
+                .map(|c| format!("namespace {} {{{}", c, newline))
+                .collect(),
+        }
+    }
+
+    /// The lines closing the namespace, with comments naming what they close.
+    pub fn close(&self, style: NamespaceStyle, newline: &str) -> String {
+        match style {
+            _ if self.is_global() => String::new(),
+            NamespaceStyle::Nested => format!("}} // namespace {}{}", self, newline),
+            NamespaceStyle::Classic => self
+                .components
+                .iter()
+                .rev()
+                .map(|c| format!("}} // namespace {}{}", c, newline))
+                .collect(),
+        }
+    }
+
+    /// Place `body` inside the namespace, separated by blank lines.
+    ///
+    /// @post The global namespace returns `body` unchanged.
+    pub fn wrap(&self, body: &str, style: NamespaceStyle, newline: &str) -> String {
+        if self.is_global() {
+            return body.to_string();
+        }
+        let mut text = self.open(style, newline);
+        text.push_str(newline);
+        for line in body.trim_matches(['\r', '\n']).lines() {
+            text.push_str(line);
+            text.push_str(newline);
+        }
+        if !body.trim_matches(['\r', '\n']).is_empty() {
+            text.push_str(newline);
+        }
+        text.push_str(&self.close(style, newline));
+        text
+    }
+}
+
+impl fmt::Display for Namespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.components.join("::"))
+    }
+}
+
+pub(crate) fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn is_keyword(ident: &str) -> bool {
+    KEYWORDS.contains(&ident)
+}
+
+/// Turn a directory name into an identifier.
+pub(crate) fn sanitize(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !ident.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        ident.insert(0, '_');
+    }
+    ident
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn parses_and_derives_namespaces() {
+        assert_eq!(
+            Namespace::parse("acme::net").unwrap().to_string(),
+            "acme::net"
+        );
+        assert!(Namespace::parse("").unwrap().is_global());
+        assert!(Namespace::parse("acme::1net").is_err());
+        assert!(Namespace::parse("acme::").is_err());
+        assert!(Namespace::parse("acme::new").is_err());
+        assert!(Namespace::parse("and::net").is_err());
+
+        let derived = |path: &str| Namespace::from_path(Path::new(path)).to_string();
+        assert_eq!(derived("include/acme/net/socket.hpp"), "acme::net");
+        assert_eq!(derived("lib/include/acme/socket.hpp"), "acme");
+        assert_eq!(derived("net-utils/2d/point.hpp"), "net_utils::_2d");
+        assert_eq!(derived("socket.hpp"), "");
+        assert_eq!(derived("include/new/export/x.hpp"), "new_::export_");
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn renders_both_styles() {
+        let ns = Namespace::parse("acme::net").unwrap();
+
+        assert_eq!(
+            ns.open(NamespaceStyle::Nested, "\n"),
+            "namespace acme::net {\n"
+        );
+        assert_eq!(
+            ns.close(NamespaceStyle::Nested, "\n"),
+            "} // namespace acme::net\n"
+        );
+        assert_eq!(
+            ns.open(NamespaceStyle::Classic, "\n"),
+            "namespace acme {\nnamespace net {\n"
+        );
+        assert_eq!(
+            ns.close(NamespaceStyle::Classic, "\n"),
+            "} // namespace net\n} // namespace acme\n"
+        );
+        assert_eq!(Namespace::default().open(NamespaceStyle::Classic, "\n"), "");
+        assert_eq!(
+            ns.wrap("", NamespaceStyle::Nested, "\r\n"),
+            "namespace acme::net {\r\n\r\n} // namespace acme::net\r\n"
+        );
+        assert_eq!(
+            ns.wrap("int x;\n", NamespaceStyle::Nested, "\n"),
+            "namespace acme::net {\n\nint x;\n\n} // namespace acme::net\n"
+        );
+    }
+}
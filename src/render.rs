@@ -0,0 +1,313 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! Allocation-free rendering of include guards into a caller-supplied writer.
+
+use crate::linkage::Linkage;
+use crate::{Language, LineEnding};
+use std::fmt::{self, Write as _};
+use std::io;
+
+/// Lines opening the Objective-C nullability region; Foundation defines its macros.
+const OBJC_OPEN_LINES: [&str; 4] = [
+    "", // blank line
+    "#import <Foundation/Foundation.h>",
+    "",
+    "NS_ASSUME_NONNULL_BEGIN",
+];
+
+/// Lines closing the Objective-C nullability region.
+const OBJC_CLOSE_LINES: [&str; 2] = [
+    "NS_ASSUME_NONNULL_END",
+    "", // blank line
+];
+
+/// The block a language places around the header body.
+pub(crate) enum BodyBlock<'a> {
+    /// The C linkage block.
+    Linkage(&'a Linkage),
+    /// A fixed block of the language.
+    Lines {
+        open: &'static [&'static str],
+        close: &'static [&'static str],
+    },
+}
+
+impl<'a> BodyBlock<'a> {
+    /// The block of `language`, if it has one; `linkage` is the C linkage block.
+    pub(crate) fn of(language: Language, linkage: &'a Linkage) -> Option<Self> {
+        match (language, linkage) {
+            (Language::C, _) => Some(BodyBlock::Linkage(linkage)),
+            (Language::ObjC | Language::ObjCxx, _) => Some(BodyBlock::Lines {
+                open: &OBJC_OPEN_LINES,
+                close: &OBJC_CLOSE_LINES,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Write the opening half of the block, starting with a blank line.
+    pub(crate) fn write_open<W: fmt::Write + ?Sized>(
+        &self,
+        out: &mut W,
+        newline: &str,
+    ) -> fmt::Result {
+        match self {
+            BodyBlock::Linkage(linkage) => linkage.write_open(out, newline),
+            BodyBlock::Lines { open, .. } => write_lines(out, open, newline),
+        }
+    }
+
+    /// Write the closing half of the block, ending with a blank line.
+    pub(crate) fn write_close<W: fmt::Write + ?Sized>(
+        &self,
+        out: &mut W,
+        newline: &str,
+    ) -> fmt::Result {
+        match self {
+            BodyBlock::Linkage(linkage) => linkage.write_close(out, newline),
+            BodyBlock::Lines { close, .. } => write_lines(out, close, newline),
+        }
+    }
+}
+
+/// Macro the C preprocessor defines when it runs for the assembler (`.S` files).
+const ASSEMBLER_MACRO: &str = "__ASSEMBLER__";
+
+/// Write the check hiding the C-only parts of a header from the assembler,
+/// starting with a blank line.
+pub(crate) fn write_assembler_open<W: fmt::Write + ?Sized>(
+    out: &mut W,
+    newline: &str,
+) -> fmt::Result {
+    write!(out, "{}#ifndef {}{}", newline, ASSEMBLER_MACRO, newline)
+}
+
+/// Write the end of the assembler check, ending with a blank line.
+pub(crate) fn write_assembler_close<W: fmt::Write + ?Sized>(
+    out: &mut W,
+    language: Language,
+    newline: &str,
+) -> fmt::Result {
+    let (open, close) = endif_comment_delimiters(language);
+    write!(
+        out,
+        "#endif {}{}{}{}{}",
+        open, ASSEMBLER_MACRO, close, newline, newline
+    )
+}
+
+/// Write each of `lines` followed by `newline`.
+fn write_lines<W: fmt::Write + ?Sized>(out: &mut W, lines: &[&str], newline: &str) -> fmt::Result {
+    for line in lines {
+        out.write_str(line)?;
+        out.write_str(newline)?;
+    }
+    Ok(())
+}
+
+/// Guard macro name `<prefix>_<UUID>[_<suffix>]`, formatted without allocating.
+///
+/// `uuid` is the uppercase hyphenated form; dashes are written as underscores.
+struct GuardName<'a> {
+    prefix: &'a str,
+    uuid: &'a str,
+    suffix: Option<&'a str>,
+}
+
+impl fmt::Display for GuardName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.prefix)?;
+        for group in self.uuid.split('-') {
+            f.write_char('_')?;
+            f.write_str(group)?;
+        }
+        if let Some(suffix) = self.suffix {
+            f.write_char('_')?;
+            f.write_str(suffix)?;
+        }
+        Ok(())
+    }
+}
+
+/// Format the guard macro name for `uuid` into a new `String`.
+pub(crate) fn guard_name(uuid: &uuid::Uuid, prefix: &str, suffix: Option<&str>) -> String {
+    let mut buffer = [0u8; uuid::fmt::Hyphenated::LENGTH];
+    let uuid = uuid.hyphenated().encode_upper(&mut buffer);
+    GuardName {
+        prefix,
+        uuid,
+        suffix,
+    }
+    .to_string()
+}
+
+/// Resolve the newline sequence for `line_ending`.
+pub(crate) fn newline(line_ending: LineEnding) -> &'static str {
+    match line_ending {
+        LineEnding::LF => "\n",
+        LineEnding::CRLF => "\r\n",
+        LineEnding::None => {
+            // Qualitative explanation: pick system default line ending.
+            if cfg!(target_os = "windows") {
+                "\r\n"
+            } else {
+                "\n"
+            }
+        }
+    }
+}
+
+/// Delimiters of a one-line comment valid for `language`.
+///
+/// C keeps to `/* */` so headers stay valid C89; C++ and shaders use `//`.
+pub(crate) fn comment_delimiters(language: Language) -> (&'static str, &'static str) {
+    match language {
+        Language::Glsl | Language::Hlsl => ("// ", ""),
+        language if language.is_cxx() => ("// ", ""),
+        _ => ("/* ", " */"),
+    }
+}
+
+/// Delimiters of the comment naming the guard after `#endif`.
+///
+/// Only shader preprocessors differ from the historical `/* */`.
+fn endif_comment_delimiters(language: Language) -> (&'static str, &'static str) {
+    match language {
+        Language::Glsl | Language::Hlsl => comment_delimiters(language),
+        _ => ("/* ", " */"),
+    }
+}
+
+/// Write the include-guard text around `uuid` into `out`.
+///
+/// @post `out` received exactly the text `render_guard` returns for the same inputs.
+pub(crate) fn write_guard<W: fmt::Write + ?Sized>(
+    out: &mut W,
+    uuid: &uuid::Uuid,
+    prefix: &str,
+    suffix: Option<&str>,
+    language: Language,
+    line_ending: LineEnding,
+) -> fmt::Result {
+    // Uppercase hyphenated form on the stack instead of a temporary `String`.
+    let mut buffer = [0u8; uuid::fmt::Hyphenated::LENGTH];
+    let uuid = uuid.hyphenated().encode_upper(&mut buffer);
+    let name = GuardName {
+        prefix,
+        uuid,
+        suffix,
+    };
+    let newline = newline(line_ending);
+
+    write_opening(out, &name, language, newline)?;
+    // Without a body only a body block needs a blank line between its two halves.
+    if BodyBlock::of(language, &Linkage::ExternC).is_some() {
+        out.write_str(newline)?;
+    }
+    write_closing(
+        out,
+        &name,
+        language,
+        &Linkage::ExternC,
+        Closing::default(),
+        newline,
+    )
+}
+
+/// Write the `#ifndef`/`#define` lines (and the opening body block) for `name`.
+pub(crate) fn write_opening<W: fmt::Write + ?Sized>(
+    out: &mut W,
+    name: &dyn fmt::Display,
+    language: Language,
+    newline: &str,
+) -> fmt::Result {
+    write!(out, "#ifndef {}{}", name, newline)?;
+    write!(out, "#define {}{}", name, newline)?;
+
+    // If the target language is C, add extern "C" compatibility blocks.
+    // This branch ensures C consumers get the correct linkage annotations.
+    if let Some(block) = BodyBlock::of(language, &Linkage::ExternC) {
+        block.write_open(out, newline)?;
+    }
+    Ok(())
+}
+
+/// Optional parts of the end of a guard; the default has none.
+/// - `assembler_safe`: Close the check hiding the body from the assembler.
+/// - `strict`: Add the `#else` branch rejecting a second inclusion.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Closing {
+    pub(crate) assembler_safe: bool,
+    pub(crate) strict: bool,
+}
+
+/// Write the closing body block (if any), the optional parts of `closing`
+/// and the `#endif` line for `name`.
+pub(crate) fn write_closing<W: fmt::Write + ?Sized>(
+    out: &mut W,
+    name: &dyn fmt::Display,
+    language: Language,
+    linkage: &Linkage,
+    closing: Closing,
+    newline: &str,
+) -> fmt::Result {
+    if let Some(block) = BodyBlock::of(language, linkage) {
+        block.write_close(out, newline)?;
+    }
+    if closing.assembler_safe {
+        write_assembler_close(out, language, newline)?;
+    }
+    if closing.strict {
+        write!(out, "#else{}", newline)?;
+        write!(out, "#error \"This header was included twice.\"{}", newline)?;
+    }
+    let (open, close) = endif_comment_delimiters(language);
+    write!(out, "#endif {}{}{}{}", open, name, close, newline)
+}
+
+/// Assemble the include-guard text around `uuid` into a new `String`.
+pub(crate) fn render_guard(
+    uuid: &uuid::Uuid,
+    prefix: &str,
+    suffix: Option<&str>,
+    language: Language,
+    line_ending: LineEnding,
+) -> String {
+    let mut text = String::with_capacity(256);
+    write_guard(&mut text, uuid, prefix, suffix, language, line_ending)
+        .expect("writing to a String cannot fail");
+    text
+}
+
+/// Adapter forwarding `fmt::Write` calls to an `io::Write`, keeping the first I/O error.
+///
+/// This mirrors how `io::Write::write_fmt` surfaces errors from the underlying writer.
+pub(crate) struct IoAdapter<'a, W: io::Write + ?Sized> {
+    inner: &'a mut W,
+    error: Option<io::Error>,
+}
+
+impl<'a, W: io::Write + ?Sized> IoAdapter<'a, W> {
+    pub(crate) fn new(inner: &'a mut W) -> Self {
+        IoAdapter { inner, error: None }
+    }
+
+    /// Convert a formatting result into the underlying I/O result.
+    pub(crate) fn finish(self, result: fmt::Result) -> io::Result<()> {
+        match (result, self.error) {
+            (Ok(()), _) => Ok(()),
+            (Err(_), Some(e)) => Err(e),
+            (Err(_), None) => Err(io::Error::other("formatter error")),
+        }
+    }
+}
+
+impl<W: io::Write + ?Sized> fmt::Write for IoAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
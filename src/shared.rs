@@ -0,0 +1,156 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! Thread-safe generator usable through `&self`.
+
+use crate::render::{self, render_guard};
+use crate::{GuardOptions, IncludeGuardGenerator, Language, LineEnding, UuidKind};
+use std::io;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Include guard generator that can be shared between threads.
+///
+/// Only drawing the next UUID is serialized; rendering happens outside the lock,
+/// so threads mostly run in parallel.
+///
+/// The lock is deliberate rather than a stand-in for atomics: the v7 state is a
+/// 48-bit millisecond plus a 42-bit counter (90 bits, wider than any stable
+/// atomic), injected `Clock`/`RandomSource` implementations take `&mut self`,
+/// and the state file needs exclusive access anyway. What this type adds over a
+/// caller-side `Mutex<IncludeGuardGenerator>` is a `Sync` API through `&self`,
+/// a critical section limited to drawing the UUID, and poison recovery.
+///
+/// @invariant All threads draw from one `IncludeGuardGenerator`, so v7 guards are
+///            pairwise distinct and each thread observes strictly increasing values.
+pub struct SharedIncludeGuardGenerator {
+    inner: Mutex<IncludeGuardGenerator>,
+}
+
+impl SharedIncludeGuardGenerator {
+    /// Create a shared generator with the default clock and randomness.
+    pub fn new() -> Self {
+        Self::from(IncludeGuardGenerator::new())
+    }
+
+    /// Return the wrapped generator.
+    pub fn into_inner(self) -> IncludeGuardGenerator {
+        self.inner
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Generate the include guard string; see `IncludeGuardGenerator::generate`.
+    pub fn generate(
+        &self,
+        prefix: &str,
+        suffix: Option<&str>,
+        language: Language,
+        line_ending: LineEnding,
+        uuid_kind: UuidKind,
+    ) -> String {
+        self.try_generate(prefix, suffix, language, line_ending, uuid_kind)
+            .expect("failed to update the UUID v7 state file")
+    }
+
+    /// Generate the include guard string; see `IncludeGuardGenerator::try_generate`.
+    pub fn try_generate(
+        &self,
+        prefix: &str,
+        suffix: Option<&str>,
+        language: Language,
+        line_ending: LineEnding,
+        uuid_kind: UuidKind,
+    ) -> io::Result<String> {
+        let uuid = self.lock().next_uuid(uuid_kind)?;
+        Ok(render_guard(&uuid, prefix, suffix, language, line_ending))
+    }
+
+    /// Generate an include guard into `out`; see `IncludeGuardGenerator::write_guard`.
+    pub fn write_guard<W: std::fmt::Write + ?Sized>(
+        &self,
+        out: &mut W,
+        prefix: &str,
+        suffix: Option<&str>,
+        language: Language,
+        line_ending: LineEnding,
+        uuid_kind: UuidKind,
+    ) -> io::Result<()> {
+        let uuid = self.lock().next_uuid(uuid_kind)?;
+        render::write_guard(out, &uuid, prefix, suffix, language, line_ending)
+            .map_err(|_| io::Error::other("formatter error"))
+    }
+
+    /// Generate `count` distinct guards; see `IncludeGuardGenerator::generate_many`.
+    ///
+    /// The lock is held for the whole batch so its v7 guards are consecutive.
+    pub fn generate_many(&self, count: usize, options: &GuardOptions) -> io::Result<Vec<String>> {
+        self.lock().generate_many(count, options)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, IncludeGuardGenerator> {
+        // A panic while drawing a UUID leaves the generator consistent, so a
+        // poisoned lock is still safe to use.
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for SharedIncludeGuardGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<IncludeGuardGenerator> for SharedIncludeGuardGenerator {
+    /// Share an already configured generator (clock, randomness, state file, ordering).
+    fn from(generator: IncludeGuardGenerator) -> Self {
+        SharedIncludeGuardGenerator {
+            inner: Mutex::new(generator),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    #[test]
+    fn stress_many_threads() {
+        const THREADS: usize = 16;
+        const PER_THREAD: usize = 200;
+
+        let generator = std::sync::Arc::new(SharedIncludeGuardGenerator::new());
+        let handles: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let generator = std::sync::Arc::clone(&generator);
+                std::thread::spawn(move || {
+                    (0..PER_THREAD)
+                        .map(|_| {
+                            let text = generator.generate(
+                                "TEST",
+                                None,
+                                Language::None,
+                                LineEnding::LF,
+                                UuidKind::V7,
+                            );
+                            // Compiling the regex per guard would dominate the runtime.
+                            text.lines().next().unwrap()["#ifndef TEST_".len()..].to_string()
+                        })
+                        .collect::<Vec<String>>()
+                })
+            })
+            .collect();
+
+        let mut all = std::collections::HashSet::new();
+        for handle in handles {
+            let uuids = handle.join().unwrap();
+            // Each thread sees strictly increasing v7 guards...
+            assert!(uuids.windows(2).all(|pair| pair[0] < pair[1]));
+            // ...and no guard is ever handed to two callers.
+            for uuid in uuids {
+                assert!(all.insert(uuid), "Duplicate UUID across threads");
+            }
+        }
+        assert_eq!(all.len(), THREADS * PER_THREAD);
+    }
+}
@@ -0,0 +1,139 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! Clock and randomness sources used by `IncludeGuardGenerator`.
+//!
+//! The generator reads time and random bits only through the `Clock` and
+//! `RandomSource` traits, so callers can pin both for snapshot testing or
+//! reproducible builds. Closures implement the traits directly.
+
+/// Source of the current Unix time.
+pub trait Clock {
+    /// Return the current Unix time as `(seconds, subsecond nanoseconds)`.
+    ///
+    /// @post The nanosecond part is below `1_000_000_000`.
+    fn now(&mut self) -> (u64, u32);
+}
+
+impl<F> Clock for F
+where
+    F: FnMut() -> (u64, u32),
+{
+    fn now(&mut self) -> (u64, u32) {
+        self()
+    }
+}
+
+/// Source of the random bits placed in generated UUIDs.
+pub trait RandomSource {
+    /// Fill `dest` entirely with random bytes.
+    fn fill_bytes(&mut self, dest: &mut [u8]);
+}
+
+impl<F> RandomSource for F
+where
+    F: FnMut(&mut [u8]),
+{
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self(dest)
+    }
+}
+
+/// Random source backed by the operating system (or `crypto.getRandomValues` on wasm).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OsRandom;
+
+impl RandomSource for OsRandom {
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        // The `uuid` crate panics in the same situation; there is no sensible fallback.
+        getrandom::fill(dest).expect("OS random source is unavailable");
+    }
+}
+
+/// Clock reading the system wall clock with nanosecond resolution.
+///
+/// On wasm the clock is `Date.now()`, which only has millisecond resolution.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&mut self) -> (u64, u32) {
+        unix_time()
+    }
+}
+
+/// Clock that always returns the same instant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedClock {
+    seconds: u64,
+    nanos: u32,
+}
+
+impl FixedClock {
+    /// Create a clock frozen at `seconds` plus `nanos` after the Unix epoch.
+    ///
+    /// @pre `nanos < 1_000_000_000`.
+    pub fn new(seconds: u64, nanos: u32) -> Self {
+        debug_assert!(nanos < 1_000_000_000);
+        FixedClock { seconds, nanos }
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&mut self) -> (u64, u32) {
+        (self.seconds, self.nanos)
+    }
+}
+
+/// Small deterministic pseudo-random generator (SplitMix64).
+///
+/// Not cryptographically secure; it only needs to spread a user-supplied seed
+/// over the random bits of a UUID reproducibly.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    /// Create a generator whose output is fully determined by `seed`.
+    pub fn new(seed: u64) -> Self {
+        SeededRng { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+impl RandomSource for SeededRng {
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_be_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+fn unix_time() -> (u64, u32) {
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    {
+        // `js_sys::Date::now()` returns milliseconds since the epoch as an `f64`.
+        // Convert to integer milliseconds, then split into seconds and nanoseconds.
+        let unix_ms = js_sys::Date::now().floor() as u64;
+        let seconds = unix_ms / 1000u64;
+        let nanos = ((unix_ms % 1000) as u32) * 1_000_000u32;
+        (seconds, nanos)
+    }
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("Time went backwards");
+        // Keep the full nanosecond part; v7 generation decides how much of it to use.
+        (now.as_secs(), now.subsec_nanos())
+    }
+}
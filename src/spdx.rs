@@ -0,0 +1,157 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! SPDX license and copyright lines at the top of headers.
+
+use crate::Language;
+use crate::render::{self, comment_delimiters};
+
+const LICENSE_TAG: &str = "SPDX-License-Identifier:";
+const COPYRIGHT_TAG: &str = "SPDX-FileCopyrightText:";
+
+/// The SPDX lines to emit. `None` fields are not emitted.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SpdxHeader {
+    /// SPDX license expression, e.g. `MIT` or `Apache-2.0 OR MIT`.
+    pub license: Option<String>,
+    /// Copyright text, e.g. `2026 Jane Doe`.
+    pub copyright: Option<String>,
+}
+
+impl SpdxHeader {
+    /// Whether there is nothing to emit.
+    pub fn is_empty(&self) -> bool {
+        self.license.is_none() && self.copyright.is_none()
+    }
+
+    /// The lines as comments for `language`, copyright first, each ending with `newline`.
+    pub fn render(&self, language: Language, newline: &str) -> String {
+        self.lines(language, true, true)
+            .map(|line| line + newline)
+            .collect()
+    }
+
+    /// The selected lines as comments for `language`, without line terminators.
+    fn lines(
+        &self,
+        language: Language,
+        copyright: bool,
+        license: bool,
+    ) -> impl Iterator<Item = String> {
+        let (open, close) = comment_delimiters(language);
+        let copyright = self
+            .copyright
+            .as_deref()
+            .filter(|_| copyright)
+            .map(move |text| format!("{}{} {}{}", open, COPYRIGHT_TAG, text, close));
+        let license = self
+            .license
+            .as_deref()
+            .filter(|_| license)
+            .map(move |text| format!("{}{} {}{}", open, LICENSE_TAG, text, close));
+        copyright.into_iter().chain(license)
+    }
+}
+
+/// Add the lines of `spdx` that `text` lacks.
+///
+/// A tag counts as present anywhere in the leading comment block, in any
+/// comment style. Missing lines go directly before the first existing SPDX
+/// line, or at the top of the file followed by a blank line. The newline
+/// style is taken from `text`.
+///
+/// @post Applying it twice gives the same text as applying it once.
+pub fn insert_missing(text: &str, spdx: &SpdxHeader, language: Language) -> String {
+    let newline = if text.contains("\r\n") {
+        "\r\n"
+    } else if text.contains('\n') {
+        "\n"
+    } else {
+        render::newline(crate::LineEnding::None)
+    };
+
+    // The leading block of comments and blank lines.
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let banner = lines
+        .iter()
+        .position(|line| {
+            let line = line.trim();
+            !(line.is_empty()
+                || line.starts_with("//")
+                || line.starts_with("/*")
+                || line.starts_with('*'))
+        })
+        .unwrap_or(lines.len());
+    let has = |tag: &str| lines[..banner].iter().any(|line| line.contains(tag));
+    let missing: Vec<String> = spdx
+        .lines(language, !has(COPYRIGHT_TAG), !has(LICENSE_TAG))
+        .collect();
+    if missing.is_empty() {
+        return text.to_string();
+    }
+
+    let at = lines[..banner]
+        .iter()
+        .position(|line| line.contains(COPYRIGHT_TAG) || line.contains(LICENSE_TAG));
+    let mut out = String::with_capacity(text.len() + 128);
+    let split = at.unwrap_or(0);
+    out.extend(lines[..split].iter().copied());
+    for line in &missing {
+        out.push_str(line);
+        out.push_str(newline);
+    }
+    if at.is_none() && !text.is_empty() && !lines[0].trim().is_empty() {
+        out.push_str(newline);
+    }
+    out.extend(lines[split..].iter().copied());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    use wasm_bindgen_test::*;
+
+    fn spdx() -> SpdxHeader {
+        SpdxHeader {
+            license: Some("MIT".to_string()),
+            copyright: Some("2026 Jane Doe".to_string()),
+        }
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn renders_comment_style_per_language() {
+        assert_eq!(
+            spdx().render(Language::Cxx, "\n"),
+            "// SPDX-FileCopyrightText: 2026 Jane Doe\n// SPDX-License-Identifier: MIT\n"
+        );
+        assert_eq!(
+            spdx().render(Language::C, "\n"),
+            "/* SPDX-FileCopyrightText: 2026 Jane Doe */\n/* SPDX-License-Identifier: MIT */\n"
+        );
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn inserts_only_missing_lines() {
+        let bare = "#ifndef G\r\n#define G\r\n#endif\r\n";
+        let added = insert_missing(bare, &spdx(), Language::Cxx);
+        assert_eq!(
+            added,
+            "// SPDX-FileCopyrightText: 2026 Jane Doe\r\n// SPDX-License-Identifier: MIT\r\n\r\n"
+                .to_string()
+                + bare
+        );
+        assert_eq!(insert_missing(&added, &spdx(), Language::C), added);
+
+        let partial = "/* banner */\n/* SPDX-License-Identifier: BSD-3-Clause */\n\nint x;\n";
+        assert_eq!(
+            insert_missing(partial, &spdx(), Language::C),
+            "/* banner */\n/* SPDX-FileCopyrightText: 2026 Jane Doe */\n\
+             /* SPDX-License-Identifier: BSD-3-Clause */\n\nint x;\n"
+        );
+    }
+}
@@ -0,0 +1,129 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! Persistent UUID v7 ordering state shared between processes.
+//!
+//! The file stores the last-issued millisecond timestamp and counter as a
+//! single text line (`<millis> <counter>`). Every reservation takes an
+//! exclusive lock on the file, so concurrent `guardgen` invocations on one
+//! machine observe each other and always issue strictly increasing values.
+
+use crate::v7;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Lock-protected file recording the last-issued v7 timestamp and counter.
+///
+/// @invariant Successive reservations through any handle on the same path return
+///            strictly increasing `(millis, counter)` pairs.
+#[derive(Debug, Clone)]
+pub struct V7StateFile {
+    path: PathBuf,
+}
+
+impl V7StateFile {
+    /// Use `path` as the state file, creating it (empty) if it does not exist.
+    ///
+    /// @post The file exists and is readable and writable by this process.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        Self::open_file(&path)?;
+        Ok(V7StateFile { path })
+    }
+
+    /// Path of the state file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reserve the next `(millis, counter)` pair for a clock reading of `millis`.
+    ///
+    /// `start` is the counter used when the millisecond advances; `floor` is the
+    /// smallest counter acceptable within the current millisecond (non-zero when
+    /// the counter's top bits carry a sub-millisecond fraction).
+    ///
+    /// @pre `start` and `floor` fit in the 42 counter bits.
+    /// @post The returned pair is strictly greater than every pair previously
+    ///       reserved through this file, and `counter` fits in `COUNTER_BITS` bits.
+    pub(crate) fn reserve(&self, millis: u64, start: u64, floor: u64) -> io::Result<(u64, u64)> {
+        let mut file = Self::open_file(&self.path)?;
+        file.lock()?;
+
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+        let last = parse_state(&contents).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("malformed v7 state file '{}'", self.path.display()),
+            )
+        })?;
+
+        let next = v7::advance(last, millis, start, floor);
+
+        file.seek(SeekFrom::Start(0))?;
+        file.set_len(0)?;
+        writeln!(file, "{} {}", next.0, next.1)?;
+        file.sync_data()?;
+        file.unlock()?;
+
+        Ok(next)
+    }
+
+    fn open_file(path: &Path) -> io::Result<File> {
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+    }
+}
+
+/// Parse the state line; an empty file means nothing has been issued yet.
+fn parse_state(contents: &str) -> Option<Option<(u64, u64)>> {
+    let contents = contents.trim();
+    if contents.is_empty() {
+        return Some(None);
+    }
+    let (millis, counter) = contents.split_once(' ')?;
+    Some(Some((millis.parse().ok()?, counter.parse().ok()?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserve_persists_between_handles() {
+        let path = std::env::temp_dir().join(format!(
+            "guardgen-state-test-{}-{}",
+            std::process::id(),
+            line!()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let first = V7StateFile::open(&path)
+            .unwrap()
+            .reserve(100, 7, 0)
+            .unwrap();
+        let second = V7StateFile::open(&path)
+            .unwrap()
+            .reserve(100, 7, 0)
+            .unwrap();
+        let third = V7StateFile::open(&path).unwrap().reserve(50, 7, 0).unwrap();
+
+        assert_eq!(first, (100, 7));
+        assert_eq!(second, (100, 8));
+        assert_eq!(third, (100, 9));
+
+        std::fs::write(&path, "garbage").unwrap();
+        let err = V7StateFile::open(&path)
+            .unwrap()
+            .reserve(100, 7, 0)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
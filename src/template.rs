@@ -0,0 +1,334 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! Header skeletons built from user-defined templates.
+//!
+//! A template is the text of a new header with `{{name}}` placeholders:
+//!
+//! - `{{guard_open}}`, `{{guard_close}}`: the guard's opening and closing parts
+//! - `{{guard}}`: the guard macro name
+//! - `{{file_name}}`: the header's file name
+//! - `{{date}}` (`YYYY-MM-DD`), `{{year}}`
+//! - `{{author}}`, `{{project}}`
+//! - `{{namespace_open}}`, `{{namespace_close}}`: the C++ namespace lines
+//! - `{{namespace}}`: the namespace name, e.g. `acme::net`
+//!
+//! A template without guard placeholders is the body of the header and is
+//! placed between the guard lines.
+//!
+//! Only `{{` followed by an identifier and `}}` is a placeholder; any other
+//! `{{`, such as the aggregate initialiser in `int a[2][2] = {{1, 2}, {3, 4}};`,
+//! is kept as is. Braces around a lone identifier, e.g. `{{x}}`, need a space:
+//! `{ {x} }`.
+
+use crate::IncludeGuard;
+use crate::namespace::{Namespace, NamespaceStyle, is_identifier};
+use std::fmt;
+use std::ops::Range;
+
+/// Values substituted for the non-guard placeholders.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TemplateValues {
+    pub file_name: String,
+    /// Date in `YYYY-MM-DD` form (with more digits after the year 9999);
+    /// `{{year}}` is taken from it.
+    pub date: String,
+    pub author: String,
+    pub project: String,
+    /// Namespace of the header; the global namespace leaves its placeholders empty.
+    pub namespace: Namespace,
+    pub namespace_style: NamespaceStyle,
+}
+
+/// Error in a template.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TemplateError {
+    /// `{{name}}` is not a known placeholder.
+    UnknownPlaceholder(String),
+    /// A `{{` is never closed (1-based line number).
+    UnterminatedPlaceholder(usize),
+    /// Only one of `{{guard_open}}` and `{{guard_close}}` is used, or one is used twice.
+    UnbalancedGuard,
+    /// Only one of `{{namespace_open}}` and `{{namespace_close}}` is used, or one is used twice.
+    UnbalancedNamespace,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::UnknownPlaceholder(name) => {
+                write!(f, "unknown placeholder '{{{{{}}}}}'", name)
+            }
+            TemplateError::UnterminatedPlaceholder(line) => {
+                write!(f, "unterminated placeholder on line {}", line)
+            }
+            TemplateError::UnbalancedGuard => write!(
+                f,
+                "'{{{{guard_open}}}}' and '{{{{guard_close}}}}' must each appear exactly once"
+            ),
+            TemplateError::UnbalancedNamespace => write!(
+                f,
+                "'{{{{namespace_open}}}}' and '{{{{namespace_close}}}}' must each appear exactly once"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// A parsed header template.
+///
+/// @invariant Every placeholder in `text` is known, and the guard and namespace
+///            placeholders are each either both absent or both present exactly once.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Template {
+    text: String,
+    has_guard: bool,
+    has_namespace: bool,
+}
+
+impl Template {
+    /// Validate `text` as a template.
+    pub fn parse(text: &str) -> Result<Self, TemplateError> {
+        let mut opens = 0;
+        let mut closes = 0;
+        let mut namespace_opens = 0;
+        let mut namespace_closes = 0;
+        for placeholder in placeholders(text) {
+            match placeholder?.1 {
+                "guard_open" => opens += 1,
+                "guard_close" => closes += 1,
+                "namespace_open" => namespace_opens += 1,
+                "namespace_close" => namespace_closes += 1,
+                "guard" | "file_name" | "date" | "year" | "author" | "project" | "namespace" => {}
+                name => return Err(TemplateError::UnknownPlaceholder(name.to_string())),
+            }
+        }
+        if (opens, closes) != (0, 0) && (opens, closes) != (1, 1) {
+            return Err(TemplateError::UnbalancedGuard);
+        }
+        if namespace_opens != namespace_closes || namespace_opens > 1 {
+            return Err(TemplateError::UnbalancedNamespace);
+        }
+        Ok(Template {
+            text: text.to_string(),
+            has_guard: opens == 1,
+            has_namespace: namespace_opens == 1,
+        })
+    }
+
+    /// Whether the template places the guard itself.
+    pub fn has_guard(&self) -> bool {
+        self.has_guard
+    }
+
+    /// Whether the template places the namespace lines.
+    pub fn has_namespace(&self) -> bool {
+        self.has_namespace
+    }
+
+    /// Render a header for `guard`.
+    ///
+    /// @post Line endings follow `guard.line_ending()`.
+    pub fn render(&self, guard: &IncludeGuard, values: &TemplateValues) -> String {
+        let newline = guard.newline();
+        let year = values.date.split('-').next().unwrap_or_default();
+        let opening = guard.opening();
+        let closing = guard.closing();
+        let namespace_open = values.namespace.open(values.namespace_style, newline);
+        let namespace_close = values.namespace.close(values.namespace_style, newline);
+        let namespace = values.namespace.to_string();
+
+        let mut out = String::with_capacity(self.text.len() + opening.len() + closing.len());
+        let mut copied = 0;
+        for placeholder in placeholders(&self.text) {
+            let (range, name) = placeholder.expect("placeholders were validated by parse");
+            out.push_str(&self.text[copied..range.start]);
+            let value = match name {
+                // Guard parts stand on their own lines; the template supplies the final newline.
+                "guard_open" => opening.trim_end_matches(['\r', '\n']),
+                "guard_close" => closing.trim_end_matches(['\r', '\n']),
+                "guard" => guard.as_str(),
+                "file_name" => &values.file_name,
+                "date" => &values.date,
+                "year" => year,
+                "author" => &values.author,
+                "project" => &values.project,
+                "namespace_open" => namespace_open.trim_end_matches(['\r', '\n']),
+                "namespace_close" => namespace_close.trim_end_matches(['\r', '\n']),
+                "namespace" => &namespace,
+                name => unreachable!("unknown placeholder '{}' passed parse", name),
+            };
+            out.push_str(value);
+            copied = range.end;
+        }
+        out.push_str(&self.text[copied..]);
+
+        if self.has_guard {
+            // The guard parts already use `newline`; bring the template's lines in line.
+            out.lines()
+                .map(|line| format!("{}{}", line, newline))
+                .collect()
+        } else {
+            guard.wrap(&out)
+        }
+    }
+}
+
+/// The byte ranges and names of the `{{identifier}}` placeholders in `text`, in order.
+///
+/// A `{{` not followed by an identifier is literal text. One followed by an
+/// identifier but never closed is an error.
+fn placeholders(text: &str) -> impl Iterator<Item = Result<(Range<usize>, &str), TemplateError>> {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        loop {
+            let start = offset + text[offset..].find("{{")?;
+            let inner = &text[start + 2..];
+            let Some(end) = inner.find("}}") else {
+                if inner
+                    .trim_start()
+                    .starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                {
+                    offset = text.len();
+                    let line = text[..start].matches('\n').count() + 1;
+                    return Some(Err(TemplateError::UnterminatedPlaceholder(line)));
+                }
+                return None;
+            };
+            let name = inner[..end].trim();
+            if is_identifier(name) {
+                offset = start + 2 + end + 2;
+                return Some(Ok((start..offset, name)));
+            }
+            offset = start + 2;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Language, LineEnding};
+
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    use wasm_bindgen_test::*;
+
+    fn values() -> TemplateValues {
+        TemplateValues {
+            file_name: "socket.h".to_string(),
+            date: "2026-10-18".to_string(),
+            author: "Jane Doe".to_string(),
+            project: "Acme".to_string(),
+            ..TemplateValues::default()
+        }
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn body_template_goes_between_guard_lines() {
+        let guard = IncludeGuard::new("G".to_string(), Language::None, LineEnding::LF);
+        let template = Template::parse("/* {{file_name}} ({{project}}) */\nint x;\n").unwrap();
+
+        assert!(!template.has_guard());
+        assert_eq!(
+            template.render(&guard, &values()),
+            "#ifndef G\n#define G\n\n/* socket.h (Acme) */\nint x;\n\n#endif /* G */\n"
+        );
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn guard_placeholders_position_the_guard() {
+        let guard = IncludeGuard::new("G".to_string(), Language::None, LineEnding::CRLF);
+        let template = Template::parse(
+            "// (c) {{year}} {{author}}\n{{guard_open}}\n// {{date}} {{guard}}\n{{guard_close}}\n",
+        )
+        .unwrap();
+
+        assert!(template.has_guard());
+        assert_eq!(
+            template.render(&guard, &values()),
+            "// (c) 2026 Jane Doe\r\n#ifndef G\r\n#define G\r\n// 2026-10-18 G\r\n#endif /* G */\r\n"
+        );
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn year_is_the_date_up_to_the_first_dash() {
+        let guard = IncludeGuard::new("G".to_string(), Language::None, LineEnding::LF);
+        let template = Template::parse(
+            "{{guard_open}}
+// {{year}}
+{{guard_close}}
+",
+        )
+        .unwrap();
+        let values = TemplateValues {
+            date: "10889-08-02".to_string(),
+            ..values()
+        };
+
+        assert_eq!(
+            template.render(&guard, &values),
+            "#ifndef G\n#define G\n// 10889\n#endif /* G */\n"
+        );
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn rejects_malformed_templates() {
+        assert_eq!(
+            Template::parse("{{nope}}"),
+            Err(TemplateError::UnknownPlaceholder("nope".to_string()))
+        );
+        assert_eq!(
+            Template::parse("ok\n{{year"),
+            Err(TemplateError::UnterminatedPlaceholder(2))
+        );
+        assert_eq!(
+            Template::parse("{{guard_open}}\n"),
+            Err(TemplateError::UnbalancedGuard)
+        );
+        assert_eq!(
+            Template::parse("{{namespace_close}}\n"),
+            Err(TemplateError::UnbalancedNamespace)
+        );
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn other_double_braces_are_literal() {
+        let guard = IncludeGuard::new("G".to_string(), Language::Cxx, LineEnding::LF);
+        let body =
+            "int a[2] = {{1,2}};\nint b[2][2] = {{1, 2}, {3, 4}}; // {{file_name}}\nint c = {{";
+        let template = Template::parse(body).unwrap();
+
+        assert_eq!(
+            template.render(&guard, &values()),
+            "#ifndef G\n#define G\n\nint a[2] = {{1,2}};\n\
+             int b[2][2] = {{1, 2}, {3, 4}}; // socket.h\nint c = {{\n\n#endif /* G */\n"
+        );
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn namespace_placeholders_place_the_namespace() {
+        let guard = IncludeGuard::new("G".to_string(), Language::Cxx, LineEnding::LF);
+        let template = Template::parse(
+            "{{namespace_open}}\nclass Socket; // {{namespace}}\n{{namespace_close}}\n",
+        )
+        .unwrap();
+        let values = TemplateValues {
+            namespace: Namespace::parse("acme::net").unwrap(),
+            ..values()
+        };
+
+        assert!(template.has_namespace());
+        assert_eq!(
+            template.render(&guard, &values),
+            "#ifndef G\n#define G\n\nnamespace acme {\nnamespace net {\nclass Socket; // acme::net\n\
+             } // namespace net\n} // namespace acme\n\n#endif /* G */\n"
+        );
+    }
+}
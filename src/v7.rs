@@ -0,0 +1,125 @@
+// SPDX-FileCopyrightText: 2026 Daisuke Nagao
+// SPDX-License-Identifier: MIT
+
+//! UUID v7 counter arithmetic shared by the in-process and persistent sequencers.
+//!
+//! A v7 UUID built here carries a 42-bit counter right after the millisecond
+//! timestamp: 12 bits in `rand_a` and 30 bits at the top of `rand_b`. With
+//! `V7Ordering::SubMillisecond` the top 12 counter bits hold the sub-millisecond
+//! fraction of the clock instead (RFC 9562 method 3).
+
+use crate::V7Ordering;
+use std::cmp;
+
+/// Number of counter bits stored in a v7 UUID (12 in `rand_a`, 30 in `rand_b`).
+pub(crate) const COUNTER_BITS: u32 = 42;
+
+const MAX_COUNTER: u64 = (1 << COUNTER_BITS) - 1;
+
+/// Shift placing the 12-bit sub-millisecond fraction at the top of the counter.
+const SUB_MILLIS_SHIFT: u32 = COUNTER_BITS - 12;
+
+// Reseeded counters leave their top bit clear (below the fraction, if any) so
+// many increments fit into one millisecond before the timestamp must advance.
+const COUNTER_RESEED_MASK: u64 = (1 << (COUNTER_BITS - 1)) - 1;
+const SUB_MILLIS_RESEED_MASK: u64 = (1 << (SUB_MILLIS_SHIFT - 1)) - 1;
+
+/// Convert a Unix time to whole milliseconds, as stored in a v7 timestamp.
+pub(crate) fn unix_millis(seconds: u64, nanos: u32) -> u64 {
+    seconds
+        .saturating_mul(1000)
+        .saturating_add(u64::from(nanos / 1_000_000))
+}
+
+/// Scale the sub-millisecond part of `nanos` to 12 bits (RFC 9562 method 3).
+fn sub_millis_fraction(nanos: u32) -> u64 {
+    u64::from(nanos % 1_000_000) * 4096 / 1_000_000
+}
+
+/// Compute the `(start, floor)` counter bounds for a clock reading of `nanos`.
+///
+/// `start` is the counter used when a new millisecond begins (derived from
+/// `random`); `floor` is the smallest counter acceptable within the current
+/// millisecond, which is non-zero only when the counter carries a fraction.
+pub(crate) fn counter_bounds(ordering: V7Ordering, nanos: u32, random: u64) -> (u64, u64) {
+    match ordering {
+        V7Ordering::Counter => (random & COUNTER_RESEED_MASK, 0),
+        V7Ordering::SubMillisecond => {
+            let fraction = sub_millis_fraction(nanos) << SUB_MILLIS_SHIFT;
+            (fraction | (random & SUB_MILLIS_RESEED_MASK), fraction)
+        }
+    }
+}
+
+/// Compute the `(millis, counter)` pair that follows `last` for a clock reading of `millis`.
+///
+/// @post The result is strictly greater than `last`.
+pub(crate) fn advance(last: Option<(u64, u64)>, millis: u64, start: u64, floor: u64) -> (u64, u64) {
+    match last {
+        Some((last_millis, last_counter)) if millis <= last_millis => {
+            // Same millisecond, or the clock went backwards: keep the stored
+            // timestamp and bump the counter, moving to the next millisecond on overflow.
+            if last_counter < MAX_COUNTER {
+                let floor = if millis == last_millis { floor } else { 0 };
+                (last_millis, cmp::max(last_counter + 1, floor))
+            } else {
+                (last_millis + 1, start)
+            }
+        }
+        _ => (millis, start),
+    }
+}
+
+/// Build a v7 UUID whose 42-bit `counter` follows the timestamp.
+///
+/// The counter fills `rand_a` and the top of `rand_b` (around the variant bits);
+/// the last four bytes of `bytes` supply the remaining random bits.
+pub(crate) fn encode(millis: u64, counter: u64, bytes: &mut [u8; 10]) -> uuid::Uuid {
+    bytes[0] = ((counter >> 38) & 0x0F) as u8;
+    bytes[1] = (counter >> 30) as u8;
+    bytes[2] = ((counter >> 24) & 0x3F) as u8;
+    bytes[3] = (counter >> 16) as u8;
+    bytes[4] = (counter >> 8) as u8;
+    bytes[5] = counter as u8;
+    uuid::Builder::from_unix_timestamp_millis(millis, bytes).into_uuid()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    use wasm_bindgen_test::*;
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn advance_is_strictly_increasing() {
+        assert_eq!(advance(None, 10, 5, 0), (10, 5));
+        assert_eq!(advance(Some((10, 5)), 10, 99, 0), (10, 6));
+        assert_eq!(advance(Some((10, 5)), 9, 99, 0), (10, 6));
+        assert_eq!(advance(Some((10, 5)), 11, 99, 0), (11, 99));
+        assert_eq!(advance(Some((10, MAX_COUNTER)), 10, 3, 0), (11, 3));
+
+        // A later sub-millisecond fraction moves the counter forward; an
+        // earlier one (or a floor from a past millisecond) never moves it back.
+        assert_eq!(advance(Some((10, 5)), 10, 99, 1 << 30), (10, 1 << 30));
+        assert_eq!(
+            advance(Some((10, 3 << 30)), 10, 99, 1 << 30),
+            (10, (3 << 30) + 1)
+        );
+        assert_eq!(advance(Some((10, 5)), 9, 99, 1 << 30), (10, 6));
+    }
+
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), wasm_bindgen_test)]
+    #[cfg_attr(not(all(target_arch = "wasm32", target_os = "unknown")), test)]
+    fn counter_bounds_place_fraction_on_top() {
+        assert_eq!(
+            counter_bounds(V7Ordering::Counter, 500_000, u64::MAX),
+            (COUNTER_RESEED_MASK, 0)
+        );
+        assert_eq!(
+            counter_bounds(V7Ordering::SubMillisecond, 500_000, u64::MAX),
+            ((0x800 << 30) | SUB_MILLIS_RESEED_MASK, 0x800 << 30)
+        );
+    }
+}